@@ -0,0 +1,214 @@
+//! Benchmarks for every day's generator and parts, plus the Intcode interpreter and the day 23
+//! network.
+//!
+//! ```text
+//! cargo bench -- [FILTER]... [--baseline NAME] [--save-baseline NAME]
+//! ```
+//!
+//! Each benchmark warms up, then takes a number of samples and reports the fastest, median and
+//! slowest time per iteration. Medians are saved to `target/benchmarks/NAME.txt` (`latest` by
+//! default) and each run reports the change against the previous save, so interpreter
+//! redesigns can be compared by saving a baseline before the change.
+
+use aoc2019::bench_support::Network;
+use aoc2019::intcode_computer::{self, Computer};
+use aoc2019::solutions::SOLUTIONS;
+use std::collections::{HashMap, VecDeque};
+use std::env;
+use std::fmt::Display;
+use std::fs;
+use std::hint::black_box;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+const WARM_UP: Duration = Duration::from_millis(200);
+const MEASUREMENT: Duration = Duration::from_secs(1);
+const MIN_SAMPLES: u32 = 5;
+const MAX_SAMPLES: u32 = 20;
+
+struct Bencher {
+    filters: Vec<String>,
+    previous: HashMap<String, f64>,
+    results: Vec<(String, f64)>,
+}
+
+impl Bencher {
+    fn bench<F: FnMut()>(&mut self, name: &str, mut routine: F) {
+        if !self.filters.is_empty() && !self.filters.iter().any(|f| name.contains(f.as_str())) {
+            return;
+        }
+
+        // Double the iterations until the warm-up time is used, to estimate a single one.
+        let mut iterations = 1u64;
+        let mut elapsed = Duration::from_secs(0);
+        let mut total = 0u64;
+        while elapsed < WARM_UP {
+            let start = Instant::now();
+            for _ in 0..iterations {
+                routine();
+            }
+            elapsed += start.elapsed();
+            total += iterations;
+            iterations *= 2;
+        }
+        let estimate = elapsed.as_nanos() as f64 / total as f64;
+
+        let sample_time = MEASUREMENT.as_nanos() as f64 / f64::from(MAX_SAMPLES);
+        let iterations = (sample_time / estimate).max(1.0) as u64;
+        let samples = ((MEASUREMENT.as_nanos() as f64 / (estimate * iterations as f64)) as u32)
+            .clamp(MIN_SAMPLES, MAX_SAMPLES);
+
+        let mut times: Vec<f64> = (0..samples)
+            .map(|_| {
+                let start = Instant::now();
+                for _ in 0..iterations {
+                    routine();
+                }
+                start.elapsed().as_nanos() as f64 / iterations as f64
+            })
+            .collect();
+        times.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let median = times[times.len() / 2];
+
+        let change = match self.previous.get(name) {
+            Some(previous) => format!("  change: {:+.2}%", (median - previous) / previous * 100.0),
+            None => String::new(),
+        };
+        println!(
+            "{:<24} time: [{} {} {}]{}",
+            name, format_time(times[0]), format_time(median), format_time(times[times.len() - 1]), change);
+        self.results.push((name.to_string(), median));
+    }
+}
+
+fn format_time(nanos: f64) -> String {
+    format!("{:.2?}", Duration::from_nanos(nanos.round() as u64))
+}
+
+fn results_path(baseline: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("target/benchmarks").join(format!("{}.txt", baseline))
+}
+
+fn load_results(baseline: &str) -> HashMap<String, f64> {
+    fs::read_to_string(results_path(baseline))
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| {
+            let mut pieces = line.split('\t');
+            Some((pieces.next()?.to_string(), pieces.next()?.parse().ok()?))
+        })
+        .collect()
+}
+
+fn save_results(baseline: &str, results: &[(String, f64)]) {
+    let path = results_path(baseline);
+    let mut saved = load_results(baseline);
+    saved.extend(results.iter().cloned());
+    let mut lines: Vec<String> = saved.iter().map(|(name, nanos)| format!("{}\t{}", name, nanos)).collect();
+    lines.sort();
+
+    fs::create_dir_all(path.parent().unwrap()).expect("Can't create benchmark directory!");
+    fs::write(&path, lines.join("\n") + "\n").expect("Can't save benchmark results!");
+}
+
+fn input(day: u32) -> Option<String> {
+    fs::read_to_string(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(format!("input/2019/day{}.txt", day))).ok()
+}
+
+fn ignore(answer: &dyn Display) {
+    black_box(answer);
+}
+
+fn bench_days(bencher: &mut Bencher) {
+    for solution in SOLUTIONS {
+        let input = match input(solution.day) {
+            Some(input) => input,
+            None => {
+                println!("day{}/part{}: no input, skipped", solution.day, solution.part);
+                continue;
+            },
+        };
+
+        // Both parts share a generator, so it's only measured once.
+        if solution.part == 1 {
+            bencher.bench(&format!("day{}/generator", solution.day), || {
+                black_box((solution.generate)(&input).is_ok());
+            });
+        }
+
+        match (solution.generate)(&input) {
+            Ok(runner) => bencher.bench(&format!("day{}/part{}", solution.day, solution.part), || runner.bench(ignore)),
+            Err(e) => println!("day{}/part{}: generator failed, skipped: {}", solution.day, solution.part, e),
+        }
+    }
+}
+
+fn bench_intcode(bencher: &mut Bencher) {
+    if let Some(input) = input(23) {
+        bencher.bench("intcode/parse_program", || {
+            black_box(intcode_computer::parse_program(&input).unwrap());
+        });
+    }
+
+    // Adds one to address 7, then jumps back to the start, forever.
+    let mut computer = Computer::initialize(&[1001, 7, 1, 7, 1105, 1, 0, 0]);
+    let mut input = VecDeque::new();
+    let mut output = VecDeque::new();
+    bencher.bench("intcode/step", || {
+        black_box(computer.step(&mut input, &mut output));
+    });
+}
+
+/// The day 23 network on its own, without either part's stopping condition: booting it until it
+/// first goes idle, and single rounds of all 50 computers with the NAT restarting traffic.
+fn bench_network(bencher: &mut Bencher) {
+    let program = match input(23) {
+        Some(input) => intcode_computer::parse_program(&input).unwrap(),
+        None => {
+            println!("network: no input, skipped");
+            return;
+        },
+    };
+
+    bencher.bench("network/boot", || {
+        let mut network = Network::initialize(&program);
+        network.run_until_idle();
+        black_box(network.is_idle());
+    });
+
+    let mut network = Network::initialize(&program);
+    bencher.bench("network/round", || {
+        network.resume_activity();
+        network.run_round();
+        black_box(network.is_idle());
+    });
+}
+
+fn main() {
+    let mut filters = vec![];
+    let mut baseline = None;
+    let mut save_baseline = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--baseline" => baseline = args.next(),
+            "--save-baseline" => save_baseline = args.next(),
+            // Passed along by `cargo bench`.
+            "--bench" => {},
+            _ => filters.push(arg),
+        }
+    }
+
+    let compare_to = baseline.clone().or_else(|| save_baseline.clone()).unwrap_or_else(|| "latest".to_string());
+    let mut bencher = Bencher { filters, previous: load_results(&compare_to), results: vec![] };
+    bench_intcode(&mut bencher);
+    bench_network(&mut bencher);
+    bench_days(&mut bencher);
+
+    // Comparing against a named baseline leaves it untouched unless asked to replace it.
+    match (save_baseline, baseline) {
+        (Some(name), _) => save_results(&name, &bencher.results),
+        (None, None) => save_results("latest", &bencher.results),
+        (None, Some(_)) => {},
+    }
+}
@@ -0,0 +1,58 @@
+//! Finds every `#[aoc(dayN, partM)]` solution in `src/` and writes a table of them for
+//! `src/solutions.rs`.
+//!
+//! Each entry calls `Factory::dayN_partM`, which `aoc_lib!` generates along with a
+//! `DayNPartM` trait per solution. That's how aoc-runner 0.2.2 wires up `cargo aoc` rather
+//! than a documented API, so both aoc-runner crates are pinned to that exact version in
+//! `Cargo.toml`. Check the generated names still match before upgrading them.
+
+use std::env;
+use std::fs;
+use std::path::Path;
+
+fn main() {
+    println!("cargo:rerun-if-changed=src");
+
+    let mut solutions = vec![];
+    for entry in fs::read_dir("src").expect("Can't read src!") {
+        let path = entry.expect("Can't read src entry!").path();
+        if path.extension().is_some_and(|e| e == "rs") {
+            let source = fs::read_to_string(&path).expect("Can't read source file!");
+            for (i, line) in source.lines().enumerate() {
+                let args = match line.trim().strip_prefix("#[aoc(") {
+                    Some(args) => args,
+                    None => continue,
+                };
+                match parse_attribute(args) {
+                    Some(solution) => solutions.extend(solution),
+                    None => panic!("{}:{}: Can't read solution attribute: {}", path.display(), i + 1, line.trim()),
+                }
+            }
+        }
+    }
+    solutions.sort();
+    solutions.dedup();
+
+    let mut table = String::from("&[\n");
+    for (day, part) in solutions {
+        table.push_str(&format!(
+            "    Solution {{ day: {day}, part: {part}, generate: |input| Factory::day{day}_part{part}(ArcStr::from(input)) }},\n",
+            day = day, part = part));
+    }
+    table.push_str("]\n");
+
+    let out = Path::new(&env::var("OUT_DIR").expect("OUT_DIR not set!")).join("solutions.rs");
+    fs::write(out, table).expect("Can't write solution table!");
+}
+
+/// The day and part from the arguments of an `#[aoc(...)]` attribute, or `Some(None)` for named
+/// alternative solutions, which aren't run. `None` if the arguments can't be read at all, so a
+/// solution can't go missing from the table unnoticed.
+fn parse_attribute(args: &str) -> Option<Option<(u32, u32)>> {
+    let args: Vec<&str> = args.strip_suffix(")]")?.split(',').map(|a| a.trim()).collect();
+    let (day, part) = match args.as_slice() {
+        [day, part] | [day, part, _] => (day.strip_prefix("day")?.parse().ok()?, part.strip_prefix("part")?.parse().ok()?),
+        _ => return None,
+    };
+    Some(if args.len() == 2 { Some((day, part)) } else { None })
+}
@@ -1,8 +1,10 @@
 use std::cmp::max;
 
+use crate::parse::{self, parse_number, ParseError};
+
 #[aoc_generator(day1)]
-fn parse(input: &str) -> Vec<i32> {
-    input.lines().filter_map(|l| l.parse().ok()).collect()
+fn parse(input: &str) -> Result<Vec<i32>, ParseError> {
+    parse::lines(input).map(|l| parse_number(input, l)).collect()
 }
 
 #[aoc(day1, part1)]
@@ -25,4 +27,30 @@ fn recursive_required_fuel(mass: i32) -> i32 {
         0 => 0,
         _ => fuel + recursive_required_fuel(fuel),
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fuel_for_module() {
+        assert_eq!(required_fuel(12), 2);
+        assert_eq!(required_fuel(14), 2);
+        assert_eq!(required_fuel(1969), 654);
+        assert_eq!(required_fuel(100756), 33583);
+    }
+
+    #[test]
+    fn fuel_including_fuel() {
+        assert_eq!(recursive_required_fuel(14), 2);
+        assert_eq!(recursive_required_fuel(1969), 966);
+        assert_eq!(recursive_required_fuel(100756), 50346);
+    }
+
+    #[test]
+    fn reports_invalid_masses() {
+        assert_eq!(parse("12\n\n14\n").unwrap(), vec![12, 14]);
+        assert_eq!(parse("12\n1x4\n").unwrap_err(), ParseError::new(2, 1, "1x4", "Invalid number"));
+    }
+}
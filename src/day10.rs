@@ -3,78 +3,92 @@ use num::Integer;
 use std::collections::HashSet;
 use std::cmp::Ordering;
 
+use crate::grid::{DenseGrid, Point};
+use crate::parse::{self, ParseError};
+
 #[aoc_generator(day10)]
-fn parse(input: &str) -> AsteroidBelt {
+fn parse(input: &str) -> Result<AsteroidBelt, ParseError> {
+    parse::check_chars(input, |c| c == '#' || c == '.', "Expected '#' or '.'")?;
     let asteroids =
-        input.lines().enumerate()
-            .flat_map(|(y, l)| l.chars().enumerate().map(move |(x, c)| (x, y, c)))
-            .filter_map(|(x, y, c)| {
-                if c == '#' {
-                    Some(Coordinate { 
-                        x: x as i64, 
-                        y: y as i64,
-                    })
-                } else {
-                    None
-                }
-            })
+        DenseGrid::parse(input, |c| c == '#').iter()
+            .filter(|(_, asteroid)| **asteroid)
+            .map(|(p, _)| Point::new(p.x as i64, p.y as i64))
             .collect();
-    AsteroidBelt { asteroids }
-}
-
-#[derive(Debug, PartialEq, Eq)]
-struct Coordinate {
-    x: i64,
-    y: i64,
+    Ok(AsteroidBelt { asteroids })
 }
 
 #[derive(Debug)]
 struct AsteroidBelt {
-    asteroids: Vec<Coordinate>,
+    asteroids: Vec<Point>,
 }
 
 #[aoc(day10, part1)]
 fn part1(asteroid_belt: &AsteroidBelt) -> usize {
-    asteroid_belt.asteroids.iter()
-        .map(|base| asteroid_belt.count_detectable(base))
-        .max()
-        .unwrap()
+    asteroid_belt.best_station().1
+}
+
+#[aoc(day10, part2)]
+fn part2(asteroid_belt: &AsteroidBelt) -> i64 {
+    let (station, _) = asteroid_belt.best_station();
+    let order = asteroid_belt.vaporize_order(station);
+    let target = order.get(199).expect("Fewer than 200 asteroids to vaporize!");
+    target.x * 100 + target.y
 }
 
 impl AsteroidBelt {
-    fn count_detectable(&self, base: &Coordinate) -> usize {
+    fn count_detectable(&self, base: &Point) -> usize {
         let mut angles = HashSet::new();
         for a in &self.asteroids {
             if base != a {
-                angles.insert(base.angle_to(&a));
+                angles.insert(Angle::between(base, a));
             }
         }
         angles.len()
     }
 
-    fn chart(&self, base: &Coordinate) -> MultiMap<Angle, (&Coordinate, i64)> {
+    fn chart(&self, base: &Point) -> MultiMap<Angle, (&Point, i64)> {
         let mut chart = MultiMap::new();
         for a in &self.asteroids {
             if base != a {
-                chart.insert(base.angle_to(&a), (a, base.distance_to(&a)));
+                chart.insert(Angle::between(base, a), (a, base.manhattan_distance(a)));
             }
         }
         chart
     }
 
-    // fn vaporize_order(&self, base: &Coordinate) {
-    //     let mut chart = self.chart(base);
-    //     // let mut order = vec![];
-    //     while chart.len() > 0 {
-    //         let mut keys: Vec<&Angle> = chart.keys().collect();
-    //         keys.sort_by(|a, b| Angle::compare(a, b));
-    //         for key in keys.iter() {
-    //             let mut asteroids = chart.get_vec_mut(key).unwrap();
-    //             let closest = asteroids.iter().min_by_key(|c| c.1).unwrap();
-    //             asteroids.retain(|e| e != closest);
-    //         }
-    //     }
-    // }
+    /// Asteroids in the order a laser at `base` vaporizes them. The laser starts pointing up
+    /// and rotates clockwise, hitting only the closest asteroid at each angle per rotation.
+    fn vaporize_order(&self, base: &Point) -> Vec<Point> {
+        let chart = self.chart(base);
+        let mut angles: Vec<(&Angle, Vec<(&Point, i64)>)> =
+            chart.iter_all()
+                .map(|(angle, asteroids)| {
+                    let mut asteroids = asteroids.clone();
+                    asteroids.sort_by_key(|(_, distance)| *distance);
+                    (angle, asteroids)
+                })
+                .collect();
+        angles.sort_by(|(a, _), (b, _)| Angle::compare(a, b));
+
+        let mut order = vec![];
+        let mut rotation = 0;
+        while order.len() < self.asteroids.len() - 1 {
+            for (_, asteroids) in angles.iter() {
+                if let Some((asteroid, _)) = asteroids.get(rotation) {
+                    order.push(**asteroid);
+                }
+            }
+            rotation += 1;
+        }
+        order
+    }
+
+    fn best_station(&self) -> (&Point, usize) {
+        self.asteroids.iter()
+            .map(|base| (base, self.count_detectable(base)))
+            .max_by_key(|(_, count)| *count)
+            .expect("No asteroids!")
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Hash)]
@@ -104,19 +118,21 @@ impl Angle {
 
         if a.quadrant() % 2 == 1 {
             if a.dy.abs() > b.dy.abs() {
-                return Ordering::Less;
+                Ordering::Less
             } else {
-                return Ordering::Greater;
+                Ordering::Greater
             }
+        } else if a.dx.abs() > b.dx.abs() {
+            Ordering::Less
         } else {
-            if a.dx.abs() > b.dx.abs() {
-                return Ordering::Less;
-            } else {
-                return Ordering::Greater;
-            }
+            Ordering::Greater
         }
     }
 
+    fn between(from: &Point, to: &Point) -> Angle {
+        Angle::of(to.y - from.y, to.x - from.x)
+    }
+
     fn of(dy: i64, dx: i64) -> Angle {
         if dy == 0 && dx == 0 {
             Angle { dy: 0, dx: 0 }
@@ -143,16 +159,94 @@ impl Angle {
     }
 }
 
-impl Coordinate {
-    fn angle_to(&self, coordinate: &Coordinate) -> Angle {
-        Angle::of(coordinate.y - self.y, coordinate.x - self.x)
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LARGE_EXAMPLE: &str = "\
+.#..##.###...#######
+##.############..##.
+.#.######.########.#
+.###.#######.####.#.
+#####.##.#.##.###.##
+..#####..#.#########
+####################
+#.####....###.#.#.##
+##.#################
+#####.##.###..####..
+..######..##.#######
+####.##.####...##..#
+.#####..#.######.###
+##...#.##########...
+#.##########.#######
+.####.#.###.###.#.##
+....##.##.###..#####
+.#.#.###########.###
+#.#.#.#####.####.###
+###.##.####.##.#..##";
+
+    fn at(x: i64, y: i64) -> Point {
+        Point::new(x, y)
     }
 
-    fn distance_to(&self, coordinate: &Coordinate) -> i64 {
-        (coordinate.y - self.y).abs() + (coordinate.x - self.x).abs()
+    #[test]
+    fn best_station() {
+        let belt = parse(".#..#\n.....\n#####\n....#\n...##").unwrap();
+        assert_eq!(belt.best_station(), (&at(3, 4), 8));
+
+        let belt = parse(LARGE_EXAMPLE).unwrap();
+        assert_eq!(belt.best_station(), (&at(11, 13), 210));
+    }
+
+    #[test]
+    fn best_station_medium_examples() {
+        for (grid, station, count) in &[
+            ("......#.#.\n#..#.#....\n..#######.\n.#.#.###..\n.#..#.....\n\
+              ..#....#.#\n#..#....#.\n.##.#..###\n##...#..#.\n.#....####", at(5, 8), 33),
+            ("#.#...#.#.\n.###....#.\n.#....#...\n##.#.#.#.#\n....#.#.#.\n\
+              .##..###.#\n..#...##..\n..##....##\n......#...\n.####.###.", at(1, 2), 35),
+            (".#..#..###\n####.###.#\n....###.#.\n..###.##.#\n##.##.#.#.\n\
+              ....###..#\n..#.#..#.#\n#..#.#.###\n.##...##.#\n.....#.#..", at(6, 3), 41),
+        ] {
+            assert_eq!(parse(grid).unwrap().best_station(), (station, *count));
+        }
     }
-}
 
-fn angle(a: &Coordinate, b: &Coordinate) -> Angle {
-    Angle::of(b.y - a.y, b.x - a.x)
-}
\ No newline at end of file
+    #[test]
+    fn vaporize_small_example() {
+        let belt = parse("\
+.#....#####...#..
+##...##.#####..##
+##...#...#.#####.
+..#.....#...###..
+..#.#.....#....##").unwrap();
+        let order = belt.vaporize_order(&at(8, 3));
+        assert_eq!(order[..9], [at(8, 1), at(9, 0), at(9, 1), at(10, 0), at(9, 2), at(11, 1), at(12, 1), at(11, 2), at(15, 1)]);
+        assert_eq!(order.len(), belt.asteroids.len() - 1);
+    }
+
+    #[test]
+    fn vaporize_large_example() {
+        let belt = parse(LARGE_EXAMPLE).unwrap();
+        let order = belt.vaporize_order(&at(11, 13));
+        assert_eq!(order[0], at(11, 12));
+        assert_eq!(order[1], at(12, 1));
+        assert_eq!(order[2], at(12, 2));
+        assert_eq!(order[9], at(12, 8));
+        assert_eq!(order[19], at(16, 0));
+        assert_eq!(order[49], at(16, 9));
+        assert_eq!(order[99], at(10, 16));
+        assert_eq!(order[198], at(9, 6));
+        assert_eq!(order[199], at(8, 2));
+        assert_eq!(order[200], at(10, 9));
+        assert_eq!(order[298], at(11, 1));
+        assert_eq!(part2(&belt), 802);
+    }
+
+    #[test]
+    fn vaporize_same_angle_once_per_rotation() {
+        let belt = parse("#..\n#..\n#.#\n#..").unwrap();
+        let order = belt.vaporize_order(&at(0, 3));
+        assert_eq!(order, [at(0, 2), at(2, 2), at(0, 1), at(0, 0)]);
+    }
+}
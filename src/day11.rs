@@ -1,136 +1,355 @@
 use std::collections::{HashMap, VecDeque};
+use std::error::Error;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
 
-use crate::intcode_computer::{self, Computer};
+use crate::grid::{BoundingBox, Direction, Point, SparseGrid, Turn};
+use crate::intcode_computer::{self, Computer, FrameDecoder, Session};
+use crate::ocr;
+use crate::parse::ParseError;
 
 #[aoc_generator(day11)]
-fn parse(input: &str) -> Vec<i64> {
+fn parse(input: &str) -> Result<Vec<i64>, ParseError> {
     intcode_computer::parse_program(input)
 }
 
 #[aoc(day11, part1)]
 fn part1(program: &[i64]) -> usize {
-    let mut state = RobotState {
-        position: Coordinate(0, 0),
-        direction: Direction::Up,
-        painted: HashMap::new(),
-    };
+    let mut robot = PaintingRobot::new(program, Color::Black);
+    robot.run();
+    robot.stats().panels_painted
+}
 
-    run_robot(&mut state, program);
-    state.painted.keys().len()
+#[aoc(day11, part2)]
+fn part2(program: &[i64]) -> String {
+    let mut robot = PaintingRobot::new(program, Color::White);
+    robot.run();
+    ocr::read(&robot.image())
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-struct Coordinate(i64, i64);
+/// How long each frame stays on screen when replaying a run.
+const FRAME_DELAY: Duration = Duration::from_millis(20);
+
+/// The robot's run for the registration identifier, the one that paints a picture.
+fn paint_identifier(input: &str) -> Result<PaintingRobot, ParseError> {
+    let mut robot = PaintingRobot::new(&parse(input)?, Color::White);
+    robot.run();
+    Ok(robot)
+}
+
+pub(crate) fn export(input: &str, path: &Path) -> Result<(), Box<dyn Error>> {
+    fs::write(path, paint_identifier(input)?.to_pbm())?;
+    Ok(())
+}
+
+pub(crate) fn show(input: &str, mut out: &mut dyn Write) -> Result<(), Box<dyn Error>> {
+    paint_identifier(input)?.replay(&mut out, FRAME_DELAY)?;
+    Ok(())
+}
 
-enum Direction {
-    Up, Right, Down, Left
+pub(crate) fn record(input: &str, path: &Path) -> Result<(), Box<dyn Error>> {
+    let mut robot = PaintingRobot::new(&parse(input)?, Color::White).recording();
+    robot.run();
+    robot.session().expect("Recording robots keep a session!").save(path)?;
+    Ok(())
 }
 
-impl Direction {
-    fn rotate(&self, code: i64) -> Direction {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Color {
+    Black, White
+}
+
+impl Color {
+    fn of(code: i64) -> Color {
         match code {
-            0 => self.rotate_left(),
-            1 => self.rotate_right(),
-            _ => panic!("Invalid code!"),
+            0 => Color::Black,
+            1 => Color::White,
+            _ => panic!("Invalid color!"),
         }
     }
 
-    fn rotate_right(&self) -> Direction {
+    fn code(self) -> i64 {
         match self {
-            Direction::Up => Direction::Right,
-            Direction::Right => Direction::Down,
-            Direction::Down => Direction::Left,
-            Direction::Left => Direction::Up,
+            Color::Black => 0,
+            Color::White => 1,
         }
     }
+}
 
-    fn rotate_left(&self) -> Direction {
-        match self {
-            Direction::Up => Direction::Left,
-            Direction::Right => Direction::Up,
-            Direction::Down => Direction::Right,
-            Direction::Left => Direction::Down,
-        }
+fn turn(code: i64) -> Turn {
+    match code {
+        0 => Turn::Left,
+        1 => Turn::Right,
+        _ => panic!("Invalid code!"),
     }
 }
 
-struct RobotState {
-    position: Coordinate,
-    direction: Direction,
-    painted: HashMap<Coordinate, i64>,
+/// One instruction from the brain: what the robot saw where it stood, and what it did about it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Step {
+    position: Point,
+    heading: Direction,
+    read: Color,
+    painted: Color,
+    turn: Turn,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PaintStats {
+    steps: usize,
+    /// Panels painted at least once.
+    panels_painted: usize,
+    /// Panels painted more than once.
+    panels_repainted: usize,
+    /// Paint operations that actually changed a panel's color.
+    color_changes: usize,
+    most_painted: Option<(Point, usize)>,
 }
 
-impl RobotState {
-    fn paint(&mut self, color: i64) {
-        self.painted.insert(self.position.clone(), color);
+type Instructions = FrameDecoder<(Color, Turn), fn(&[i64]) -> (Color, Turn)>;
+
+fn instructions() -> Instructions {
+    FrameDecoder::new(2, |pair| (Color::of(pair[0]), turn(pair[1])))
+}
+
+/// The hull-painting robot, driven by its Intcode brain and keeping a record of every step.
+struct PaintingRobot {
+    brain: Computer,
+    input: VecDeque<i64>,
+    output: Instructions,
+    start: Color,
+    position: Point,
+    heading: Direction,
+    hull: SparseGrid<Color>,
+    history: Vec<Step>,
+    session: Option<Session>,
+}
+
+impl PaintingRobot {
+    /// A robot facing up on a hull that's black except for the panel it starts on.
+    fn new(program: &[i64], start: Color) -> PaintingRobot {
+        let mut hull = SparseGrid::new();
+        hull.insert(Point::origin(), start);
+        PaintingRobot {
+            brain: Computer::initialize(program),
+            input: VecDeque::new(),
+            output: instructions(),
+            start,
+            position: Point::origin(),
+            heading: Direction::Up,
+            hull,
+            history: vec![],
+            session: None,
+        }
+    }
+
+    /// Records the brain's I/O from here on, so the run can be replayed later.
+    fn recording(mut self) -> PaintingRobot {
+        self.session = Some(Session::new());
+        self
+    }
+
+    fn session(&self) -> Option<&Session> {
+        self.session.as_ref()
     }
 
-    fn rotate(&mut self, code: i64) {
-        self.direction = self.direction.rotate(code);
+    fn color(&self, position: &Point) -> Color {
+        *self.hull.get(position).unwrap_or(&Color::Black)
     }
 
-    fn move_forward(&mut self) {
-        match self.direction {
-            Direction::Up => self.position = Coordinate(self.position.0, self.position.1 - 1),
-            Direction::Right => self.position = Coordinate(self.position.0 + 1, self.position.1),
-            Direction::Down => self.position = Coordinate(self.position.0, self.position.1 + 1),
-            Direction::Left => self.position = Coordinate(self.position.0 - 1, self.position.1),
+    /// Reads the current panel to the brain and carries out its next instruction, or returns
+    /// `None` once the brain halts.
+    fn step(&mut self) -> Option<Step> {
+        let read = self.color(&self.position);
+        loop {
+            if let Some(instruction) = self.output.next_frame() {
+                return Some(self.carry_out(read, instruction));
+            }
+            if self.brain.is_halted() {
+                return None;
+            }
+            if self.input.is_empty() {
+                self.input.push_back(read.code());
+            }
+            match &mut self.session {
+                Some(session) => session.record(&mut self.brain, &mut self.input, &mut self.output),
+                None => self.brain.run_with_io(&mut self.input, &mut self.output),
+            }
         }
     }
-}
 
-fn run_robot(state: &mut RobotState, program: &[i64]) {
-    let mut computer = Computer::initialize(program);
-    let mut input = VecDeque::new();
-    let mut output = VecDeque::new();
+    fn carry_out(&mut self, read: Color, (painted, turn): (Color, Turn)) -> Step {
+        let step = Step { position: self.position, heading: self.heading, read, painted, turn };
+        self.history.push(step);
+        self.hull.insert(self.position, painted);
+        self.heading = self.heading.turn(turn);
+        self.position = self.position.step(self.heading);
+        step
+    }
+
+    fn run(&mut self) -> &[Step] {
+        while self.step().is_some() {}
+        &self.history
+    }
+
+    fn stats(&self) -> PaintStats {
+        let mut counts: HashMap<Point, usize> = HashMap::new();
+        for step in &self.history {
+            *counts.entry(step.position).or_default() += 1;
+        }
+        PaintStats {
+            steps: self.history.len(),
+            panels_painted: counts.len(),
+            panels_repainted: counts.values().filter(|c| **c > 1).count(),
+            color_changes: self.history.iter().filter(|s| s.read != s.painted).count(),
+            most_painted: counts.into_iter().max_by_key(|(p, c)| (*c, std::cmp::Reverse(*p))),
+        }
+    }
 
-    while !computer.is_halted() {
-        let color = *state.painted.get(&state.position).or(Some(&0)).unwrap();
-        input.push_back(color);
-        computer.run_with_io(&mut input, &mut output);
-        let color = output.pop_front().unwrap();
-        let rotate_code = output.pop_front().unwrap();
-        state.paint(color);
-        state.rotate(rotate_code);
-        state.move_forward();
+    /// The white panels of the hull, cropped to the area that's been painted white.
+    fn image(&self) -> ocr::Image {
+        let white: Vec<Point> = self.hull.iter().filter(|(_, c)| **c == Color::White).map(|(p, _)| *p).collect();
+        match BoundingBox::of(&white) {
+            Some(bounds) => (bounds.min.y..=bounds.max.y)
+                .map(|y| (bounds.min.x..=bounds.max.x).map(|x| self.color(&Point::new(x, y)) == Color::White).collect())
+                .collect(),
+            None => vec![],
+        }
+    }
+
+    /// The final hull as a plain (ASCII) PBM file, with white panels drawn as background.
+    fn to_pbm(&self) -> String {
+        let image = self.image();
+        let width = image.first().map(|r| r.len()).unwrap_or(0);
+        let mut pbm = format!("P1\n{} {}\n", width, image.len());
+        for row in image {
+            let bits: Vec<&str> = row.iter().map(|white| if *white { "0" } else { "1" }).collect();
+            pbm.push_str(&bits.join(" "));
+            pbm.push('\n');
+        }
+        pbm
+    }
+
+    /// The hull as it looked before each step, and once more at the end, all drawn over the
+    /// same area with the robot shown as an arrow.
+    fn frames(&self) -> Vec<String> {
+        let mut positions: Vec<Point> = self.history.iter().map(|s| s.position).collect();
+        positions.push(self.position);
+        let bounds = match BoundingBox::of(&positions) {
+            Some(bounds) => bounds,
+            None => return vec![],
+        };
+
+        let mut hull = SparseGrid::new();
+        hull.insert(Point::origin(), self.start);
+        let mut frames = Vec::with_capacity(self.history.len() + 1);
+        for step in &self.history {
+            frames.push(render_frame(&hull, &bounds, step.position, step.heading));
+            hull.insert(step.position, step.painted);
+        }
+        frames.push(render_frame(&hull, &bounds, self.position, self.heading));
+        frames
+    }
+
+    /// Plays the run back in a terminal, clearing the screen between frames.
+    fn replay<W: Write>(&self, out: &mut W, delay: Duration) -> io::Result<()> {
+        for frame in self.frames() {
+            write!(out, "\x1b[2J\x1b[H{}", frame)?;
+            out.flush()?;
+            thread::sleep(delay);
+        }
+        Ok(())
     }
 }
 
-#[aoc(day11, part2)]
-fn part2(program: &[i64]) -> String {
-    let mut state = RobotState {
-        position: Coordinate(0, 0),
-        direction: Direction::Up,
-        painted: HashMap::new(),
-    };
-    state.painted.insert(Coordinate(0, 0), 1);
-
-    run_robot(&mut state, program);
-
-    let min_x = state.painted.keys().min_by_key(|k| k.0).unwrap().0;
-    let max_x = state.painted.keys().max_by_key(|k| k.0).unwrap().0 + 1;
-    let min_y = state.painted.keys().min_by_key(|k| k.1).unwrap().1;
-    let max_y = state.painted.keys().max_by_key(|k| k.1).unwrap().1 + 1;
-
-    let mut painting = vec![vec![0; (max_x - min_x) as usize]; (max_y - min_y) as usize];
-    for (coordinate, color) in state.painted.iter() {
-        let y = (coordinate.1 - min_y) as usize;
-        let x = (coordinate.0 - min_x) as usize;
-        painting[y][x] = *color;
-    }
-
-    let mut result = String::new();
-
-    result.push_str("\n");
-    for y in 0..((max_y - min_y) as usize) {
-        for x in 0..((max_x - min_x) as usize) {
-            if painting[y][x] == 0 {
-                result.push_str(" ");
-            } else {
-                result.push_str("▓");
+fn render_frame(hull: &SparseGrid<Color>, bounds: &BoundingBox, robot: Point, heading: Direction) -> String {
+    let mut frame = String::new();
+    for point in bounds.points() {
+        frame.push(if point == robot {
+            match heading {
+                Direction::Up => '^',
+                Direction::Right => '>',
+                Direction::Down => 'v',
+                Direction::Left => '<',
             }
+        } else if hull.get(&point) == Some(&Color::White) {
+            '#'
+        } else {
+            '.'
+        });
+        if point.x == bounds.max.x {
+            frame.push('\n');
+        }
+    }
+    frame
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Outputs the example's instructions, reading (and ignoring) a color before each pair.
+    fn example_program() -> Vec<i64> {
+        let pairs = [(1, 0), (0, 0), (1, 0), (1, 0), (0, 1), (1, 0), (1, 0)];
+        let mut program = vec![];
+        for (color, turn) in pairs.iter() {
+            program.extend_from_slice(&[3, 1000, 104, *color, 104, *turn]);
         }
-        result.push_str("\n");
+        program.push(99);
+        program.resize(1001, 0);
+        program
+    }
+
+    #[test]
+    fn paints_example() {
+        let mut robot = PaintingRobot::new(&example_program(), Color::Black);
+        let history = robot.run().to_vec();
+
+        assert_eq!(history.len(), 7);
+        assert_eq!(history[4], Step {
+            position: Point::new(0, 0),
+            heading: Direction::Up,
+            read: Color::White,
+            painted: Color::Black,
+            turn: Turn::Right,
+        });
+        assert_eq!(robot.stats(), PaintStats {
+            steps: 7,
+            panels_painted: 6,
+            panels_repainted: 1,
+            color_changes: 6,
+            most_painted: Some((Point::new(0, 0), 2)),
+        });
+        assert_eq!(robot.frames().last().unwrap(), ".<#\n..#\n##.\n");
+    }
+
+    #[test]
+    fn draws_example() {
+        let mut robot = PaintingRobot::new(&example_program(), Color::Black);
+        robot.run();
+        assert_eq!(robot.to_pbm(), "P1\n3 3\n1 1 0\n1 1 0\n0 0 1\n");
+
+        let mut screen = vec![];
+        robot.replay(&mut screen, Duration::from_millis(0)).unwrap();
+        let screen = String::from_utf8(screen).unwrap();
+        assert_eq!(screen.matches("\x1b[2J\x1b[H").count(), 8);
+        assert!(screen.ends_with("\x1b[2J\x1b[H.<#\n..#\n##.\n"));
     }
-    result
-}
\ No newline at end of file
+
+    #[test]
+    fn replays_camera_readings() {
+        let mut robot = PaintingRobot::new(&example_program(), Color::Black).recording();
+        robot.run();
+        let session = robot.session().unwrap();
+        assert_eq!(session.inputs().collect::<Vec<_>>(), vec![0, 0, 0, 0, 1, 0, 0]);
+        assert_eq!(session.replay(&mut Computer::initialize(&example_program())), Ok(()));
+
+        let mut changed = example_program();
+        changed[9] = 1;
+        let divergence = session.replay(&mut Computer::initialize(&changed)).unwrap_err();
+        assert_eq!(divergence.step, 5);
+    }
+}
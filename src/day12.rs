@@ -1,20 +1,26 @@
 use num::Integer;
 use regex::Regex;
 
+use crate::parse::{self, parse_number, ParseError};
+
 #[aoc_generator(day12)]
-fn parse(input: &str) -> System {
-    let position_regex = Regex::new(r"=(-?\d+).*=(-?\d+).*=(-?\d+)").unwrap();
-    let moons = 
-        input.lines()
-            .map(|l| position_regex.captures(l).unwrap())
-            .map(|c| Position { x: c[1].parse().unwrap(), y: c[2].parse().unwrap(), z: c[3].parse().unwrap() })
-            .map(|p| Moon { position: p, velocity: Velocity { x: 0, y: 0, z: 0 }})
-            .collect();
-    System { moons: moons }
+fn parse(input: &str) -> Result<System, ParseError> {
+    let position_regex = Regex::new(r"^<x=(-?\d+), y=(-?\d+), z=(-?\d+)>$").unwrap();
+    let positions =
+        parse::lines(input)
+            .map(|l| {
+                let c = position_regex.captures(l)
+                    .ok_or_else(|| ParseError::at(input, l, "Expected a position like <x=1, y=2, z=3>"))?;
+                let coordinate = |i| parse_number(input, c.get(i).unwrap().as_str());
+                Ok((coordinate(1)?, coordinate(2)?, coordinate(3)?))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+    Ok(System::new(&positions))
 }
 
+/// Moons pulling on each other along three independent axes.
 #[derive(Clone, Debug)]
-struct System {
+pub struct System {
     moons: Vec<Moon>
 }
 
@@ -45,7 +51,20 @@ fn part1(system: &System) -> i64 {
     system.total_energy()
 }
 
+#[aoc(day12, part2)]
+fn part2(system: &System) -> u64 {
+    system.find_period()
+}
+
 impl System {
+    /// A system of moons at the given `(x, y, z)` positions, all at rest.
+    pub fn new(positions: &[(i64, i64, i64)]) -> System {
+        let moons = positions.iter()
+            .map(|&(x, y, z)| Moon { position: Position { x, y, z }, velocity: Velocity { x: 0, y: 0, z: 0 } })
+            .collect();
+        System { moons }
+    }
+
     fn step(&mut self) {
         let reference = self.moons.clone();
         self.moons.iter_mut().for_each(|m| m.apply_gravity(&reference));
@@ -55,6 +74,45 @@ impl System {
     fn total_energy(&self) -> i64 {
         self.moons.iter().map(|m| m.kinetic_energy() * m.potential_energy()).sum()
     }
+
+    /// Number of steps until the system first returns to its current state.
+    ///
+    /// Each axis evolves independently of the others, so this finds the period of each axis
+    /// on its own and combines them. Steps are reversible, so every state is part of a cycle
+    /// and the first repeated state is always the current one.
+    pub fn find_period(&self) -> u64 {
+        let x = self.axis_period(|p| p.x, |v| v.x);
+        let y = self.axis_period(|p| p.y, |v| v.y);
+        let z = self.axis_period(|p| p.z, |v| v.z);
+        x.lcm(&y).lcm(&z)
+    }
+
+    fn axis_period<P, V>(&self, position: P, velocity: V) -> u64
+    where
+        P: Fn(&Position) -> i64,
+        V: Fn(&Velocity) -> i64,
+    {
+        let initial: Vec<(i64, i64)> =
+            self.moons.iter().map(|m| (position(&m.position), velocity(&m.velocity))).collect();
+        let mut state = initial.clone();
+        let mut steps = 0;
+        loop {
+            step_axis(&mut state);
+            steps += 1;
+            if state == initial {
+                return steps;
+            }
+        }
+    }
+}
+
+/// Applies one step to the positions and velocities of every moon along a single axis.
+fn step_axis(state: &mut [(i64, i64)]) {
+    let positions: Vec<i64> = state.iter().map(|(p, _)| *p).collect();
+    for (position, velocity) in state.iter_mut() {
+        *velocity += positions.iter().map(|other| (other - *position).signum()).sum::<i64>();
+        *position += *velocity;
+    }
 }
 
 impl Moon {
@@ -83,10 +141,93 @@ impl Moon {
     }
 
     fn potential_energy(&self) -> i64 {
-        return self.position.x.abs() + self.position.y.abs() + self.position.z.abs();
+        self.position.x.abs() + self.position.y.abs() + self.position.z.abs()
     }
 
     fn kinetic_energy(&self) -> i64 {
-        return self.velocity.x.abs() + self.velocity.y.abs() + self.velocity.z.abs();
+        self.velocity.x.abs() + self.velocity.y.abs() + self.velocity.z.abs()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIRST_EXAMPLE: &str = "<x=-1, y=0, z=2>\n<x=2, y=-10, z=-7>\n<x=4, y=-8, z=8>\n<x=3, y=5, z=-1>";
+    const SECOND_EXAMPLE: &str = "<x=-8, y=-10, z=0>\n<x=5, y=5, z=10>\n<x=2, y=-7, z=3>\n<x=9, y=-8, z=-3>";
+
+    fn energy_after(input: &str, steps: usize) -> i64 {
+        let mut system = parse(input).unwrap();
+        (0..steps).for_each(|_| system.step());
+        system.total_energy()
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn total_energy() {
+        assert_eq!(energy_after(FIRST_EXAMPLE, 10), 179);
+        assert_eq!(energy_after(SECOND_EXAMPLE, 100), 1940);
+    }
+
+    #[test]
+    fn axis_step_matches_full_step() {
+        let mut system = parse(FIRST_EXAMPLE).unwrap();
+        let mut xs: Vec<(i64, i64)> = system.moons.iter().map(|m| (m.position.x, m.velocity.x)).collect();
+        for _ in 0..10 {
+            system.step();
+            step_axis(&mut xs);
+        }
+        assert_eq!(xs, system.moons.iter().map(|m| (m.position.x, m.velocity.x)).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn period() {
+        assert_eq!(parse(FIRST_EXAMPLE).unwrap().find_period(), 2772);
+        assert_eq!(parse(SECOND_EXAMPLE).unwrap().find_period(), 4686774924);
+    }
+
+    fn positions(system: &System) -> Vec<(i64, i64, i64, i64, i64, i64)> {
+        system.moons.iter()
+            .map(|m| (m.position.x, m.position.y, m.position.z, m.velocity.x, m.velocity.y, m.velocity.z))
+            .collect()
+    }
+
+    fn simulated_period(system: &System) -> u64 {
+        let initial = positions(system);
+        let mut system = system.clone();
+        let mut steps = 0;
+        loop {
+            system.step();
+            steps += 1;
+            if positions(&system) == initial {
+                return steps;
+            }
+        }
+    }
+
+    #[test]
+    fn period_of_synthetic_system() {
+        let system = System::new(&[(-1, 0, 2), (2, -10, -7), (4, -8, 8), (3, 5, -1)]);
+        assert_eq!(system.find_period(), 2772);
+    }
+
+    #[test]
+    fn axis_periods() {
+        let system = parse(FIRST_EXAMPLE).unwrap();
+        assert_eq!(system.axis_period(|p| p.x, |v| v.x), 18);
+        assert_eq!(system.axis_period(|p| p.y, |v| v.y), 28);
+        assert_eq!(system.axis_period(|p| p.z, |v| v.z), 44);
+    }
+
+    #[test]
+    fn period_matches_simulation() {
+        for input in &[
+            FIRST_EXAMPLE,
+            "<x=0, y=0, z=0>",
+            "<x=0, y=5, z=-3>\n<x=1, y=5, z=2>",
+            "<x=3, y=-1, z=0>\n<x=-2, y=4, z=1>\n<x=0, y=0, z=-5>",
+        ] {
+            let system = parse(input).unwrap();
+            assert_eq!(system.find_period(), simulated_period(&system), "{}", input);
+        }
+    }
+}
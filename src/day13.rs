@@ -1,33 +1,44 @@
 use std::cmp::Ordering;
 use std::collections::VecDeque;
+use std::error::Error;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
 
-use crate::intcode_computer::{self, Computer};
+use crate::grid::{Point, SparseGrid};
+use crate::intcode_computer::{self, Computer, FrameDecoder, Patch, Session};
+use crate::parse::ParseError;
 
 #[aoc_generator(day13)]
-fn parse(input: &str) -> Vec<i64> {
+fn parse(input: &str) -> Result<Vec<i64>, ParseError> {
     intcode_computer::parse_program(input)
 }
 
 #[aoc(day13, part1)]
 fn part1(program: &[i64]) -> usize {
-    let mut computer = Computer::initialize(program);
-    let mut input = VecDeque::new();
-    let mut output = VecDeque::new();
-
-    computer.run_with_io(&mut input, &mut output);
-    let (tiles, _) = read_output(&mut output);
-    tiles.iter().filter(|t| t.kind == TileKind::Block).count()
+    let mut arcade = Arcade::new(Computer::initialize(program));
+    arcade.tick();
+    arcade.screen().count(TileKind::Block)
 }
 
-#[derive(PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum TileKind {
     Empty, Wall, Block, Paddle, Ball
 }
 
-#[allow(dead_code)]
+impl TileKind {
+    fn symbol(self) -> char {
+        match self {
+            TileKind::Empty => ' ',
+            TileKind::Wall => '█',
+            TileKind::Block => '#',
+            TileKind::Paddle => '=',
+            TileKind::Ball => 'o',
+        }
+    }
+}
+
 struct Tile {
-    x: i64,
-    y: i64,
+    position: Point,
     kind: TileKind,
 }
 
@@ -43,59 +54,276 @@ impl Tile {
         };
 
         Tile {
-            x: triple[0],
-            y: triple[1],
-            kind: kind,
+            position: Point::new(triple[0], triple[1]),
+            kind,
         }
     }
 }
 
-fn read_output(output: &mut VecDeque<i64>) -> (Vec<Tile>, i64) {
-    let mut tiles = vec![];
-    let mut score = 0;
-    while output.len() >= 3 {
-        let triple: Vec<i64> = output.drain(0..3).collect();
+enum ScreenUpdate {
+    Draw(Tile),
+    Score(i64),
+}
+
+impl ScreenUpdate {
+    fn decode(triple: &[i64]) -> ScreenUpdate {
         if triple[0] == -1 && triple[1] == 0 {
-            score = triple[2];
+            ScreenUpdate::Score(triple[2])
         } else {
-            tiles.push(Tile::parse(&triple));
+            ScreenUpdate::Draw(Tile::parse(triple))
+        }
+    }
+}
+
+fn screen_updates() -> FrameDecoder<ScreenUpdate, fn(&[i64]) -> ScreenUpdate> {
+    FrameDecoder::new(3, ScreenUpdate::decode)
+}
+
+/// Everything the game has drawn so far, along with the latest score.
+#[derive(Debug, Clone, Default)]
+struct Screen {
+    tiles: SparseGrid<TileKind>,
+    score: i64,
+}
+
+impl Screen {
+    fn apply(&mut self, update: ScreenUpdate) {
+        match update {
+            ScreenUpdate::Score(score) => self.score = score,
+            ScreenUpdate::Draw(tile) => {
+                self.tiles.insert(tile.position, tile.kind);
+            },
+        }
+    }
+
+    fn count(&self, kind: TileKind) -> usize {
+        self.tiles.iter().filter(|(_, k)| **k == kind).count()
+    }
+
+    fn find(&self, kind: TileKind) -> Option<Point> {
+        self.tiles.iter().find(|(_, k)| **k == kind).map(|(p, _)| *p)
+    }
+
+    fn render(&self) -> String {
+        let tiles = self.tiles.render(|kind| kind.map(|k| k.symbol()).unwrap_or(' '));
+        format!("Score: {}\n{}", self.score, tiles)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Joystick {
+    Left, Neutral, Right
+}
+
+impl Joystick {
+    fn code(self) -> i64 {
+        match self {
+            Joystick::Left => -1,
+            Joystick::Neutral => 0,
+            Joystick::Right => 1,
+        }
+    }
+}
+
+/// Decides where to tilt the joystick each time the game asks.
+trait Controller {
+    fn joystick(&mut self, screen: &Screen) -> Joystick;
+}
+
+/// Keeps the paddle under the ball.
+struct AutoPilot;
+
+impl Controller for AutoPilot {
+    fn joystick(&mut self, screen: &Screen) -> Joystick {
+        match (screen.find(TileKind::Ball), screen.find(TileKind::Paddle)) {
+            (Some(ball), Some(paddle)) => match ball.x.cmp(&paddle.x) {
+                Ordering::Less => Joystick::Left,
+                Ordering::Equal => Joystick::Neutral,
+                Ordering::Greater => Joystick::Right,
+            },
+            _ => Joystick::Neutral,
+        }
+    }
+}
+
+/// Reads a move per line: `a` to go left, `d` to go right, anything else to stay put. Typing
+/// `auto`, or running out of input, hands control to the auto-pilot for the rest of the game.
+struct Keyboard<R> {
+    reader: R,
+    autopilot: bool,
+}
+
+impl<R: BufRead> Keyboard<R> {
+    fn new(reader: R) -> Keyboard<R> {
+        Keyboard { reader, autopilot: false }
+    }
+}
+
+impl<R: BufRead> Controller for Keyboard<R> {
+    fn joystick(&mut self, screen: &Screen) -> Joystick {
+        if !self.autopilot {
+            let mut line = String::new();
+            match self.reader.read_line(&mut line) {
+                Ok(n) if n > 0 => match line.trim() {
+                    "a" => return Joystick::Left,
+                    "d" => return Joystick::Right,
+                    "auto" => self.autopilot = true,
+                    _ => return Joystick::Neutral,
+                },
+                _ => self.autopilot = true,
+            }
+        }
+        AutoPilot.joystick(screen)
+    }
+}
+
+/// The arcade cabinet: the game program and the screen it draws on.
+struct Arcade {
+    computer: Computer,
+    screen: Screen,
+    input: VecDeque<i64>,
+    output: FrameDecoder<ScreenUpdate, fn(&[i64]) -> ScreenUpdate>,
+    session: Option<Session>,
+}
+
+impl Arcade {
+    fn new(computer: Computer) -> Arcade {
+        Arcade {
+            computer,
+            screen: Screen::default(),
+            input: VecDeque::new(),
+            output: screen_updates(),
+            session: None,
+        }
+    }
+
+    /// Records the game's I/O from here on, so the run can be replayed later.
+    fn recording(mut self) -> Arcade {
+        self.session = Some(Session::new());
+        self
+    }
+
+    fn session(&self) -> Option<&Session> {
+        self.session.as_ref()
+    }
+
+    fn screen(&self) -> &Screen {
+        &self.screen
+    }
+
+    fn is_over(&self) -> bool {
+        self.computer.is_halted()
+    }
+
+    /// Runs the game until it wants the joystick or ends, updating the screen as it goes.
+    fn tick(&mut self) {
+        match &mut self.session {
+            Some(session) => session.record(&mut self.computer, &mut self.input, &mut self.output),
+            None => self.computer.run_with_io(&mut self.input, &mut self.output),
+        }
+        for update in self.output.drain() {
+            self.screen.apply(update);
+        }
+    }
+
+    /// Plays until the game ends, drawing each frame to `display` if there is one, and returns
+    /// the final score.
+    fn play<C: Controller>(&mut self, controller: &mut C, mut display: Option<&mut dyn Write>) -> io::Result<i64> {
+        loop {
+            self.tick();
+            if let Some(out) = display.as_mut() {
+                write!(out, "\x1b[2J\x1b[H{}", self.screen.render())?;
+                out.flush()?;
+            }
+            if self.is_over() {
+                return Ok(self.screen.score);
+            }
+            self.input.push_back(controller.joystick(&self.screen).code());
         }
     }
-    (tiles, score)
 }
 
 #[aoc(day13, part2)]
 fn part2(program: &[i64]) -> i64 {
-    let mut program = program.to_vec();
-    program[0] = 2;
+    beat_game(free_play(program))
+}
 
-    beat_game(&program)
+/// The game with quarters inserted, so it can be played to the end.
+fn free_play(program: &[i64]) -> Computer {
+    let free_play = Patch::named("free_play").set(0, 2);
+    Computer::initialize_patched(program, &[&free_play]).expect("Empty program!")
 }
 
-fn beat_game(program: &[i64]) -> i64 {
-    let mut ball_x = 0;
-    let mut paddle_x = 0;
-    let mut score = 0;
+/// Plays the game on `display`, reading moves from `controls` as `Keyboard` does.
+pub(crate) fn play(input: &str, controls: &mut dyn BufRead, display: &mut dyn Write) -> Result<(), Box<dyn Error>> {
+    let mut arcade = Arcade::new(free_play(&parse(input)?));
+    let score = arcade.play(&mut Keyboard::new(controls), Some(&mut *display))?;
+    writeln!(display, "Game over! Final score: {}", score)?;
+    Ok(())
+}
 
-    let mut computer = Computer::initialize(program);
-    let mut input = VecDeque::new();
-    let mut output = VecDeque::new();
+pub(crate) fn record(input: &str, path: &Path) -> Result<(), Box<dyn Error>> {
+    let mut arcade = Arcade::new(free_play(&parse(input)?)).recording();
+    arcade.play(&mut AutoPilot, None)?;
+    arcade.session().expect("Recording arcades keep a session!").save(path)?;
+    Ok(())
+}
 
-    while !computer.is_halted() {
-        computer.run_with_io(&mut input, &mut output);
-        let (tiles, latest_score) = read_output(&mut output);
-        score = latest_score;
+fn beat_game(computer: Computer) -> i64 {
+    Arcade::new(computer).play(&mut AutoPilot, None).expect("Headless play can't fail!")
+}
 
-        ball_x = tiles.iter().filter(|t| t.kind == TileKind::Ball).fold(ball_x, |_acc, tile| tile.x);
-        paddle_x = tiles.iter().filter(|t| t.kind == TileKind::Paddle).fold(paddle_x, |_acc, tile| tile.x);
-        
-        let joystick_input = match ball_x.cmp(&paddle_x) {
-            Ordering::Less => -1,
-            Ordering::Equal => 0,
-            Ordering::Greater => 1,
-        };
-        input.push_back(joystick_input);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Draws walls, a ball and a paddle, then moves the paddle by whatever the joystick says
+    // and scores the paddle's new position.
+    fn tiny_game() -> Vec<i64> {
+        let mut program = vec![
+            104, 0, 104, 0, 104, 1,
+            104, 2, 104, 0, 104, 4,
+            104, 1, 104, 1, 104, 3,
+            3, 100,
+            1, 100, 101, 100,
+            104, -1, 104, 0, 4, 100,
+            99,
+        ];
+        program.resize(102, 0);
+        program[101] = 1;
+        program
     }
 
-    score
-}
\ No newline at end of file
+    #[test]
+    fn plays_headless() {
+        let mut arcade = Arcade::new(Computer::initialize(&tiny_game()));
+        arcade.tick();
+        assert_eq!(arcade.screen().find(TileKind::Ball), Some(Point::new(2, 0)));
+        assert_eq!(arcade.screen().render(), "Score: 0\n█ o\n = \n");
+
+        assert_eq!(arcade.play(&mut AutoPilot, None).unwrap(), 2);
+    }
+
+    #[test]
+    fn keyboard_controls_joystick() {
+        let mut arcade = Arcade::new(Computer::initialize(&tiny_game()));
+        let mut keyboard = Keyboard::new("a\n".as_bytes());
+        assert_eq!(arcade.play(&mut keyboard, None).unwrap(), 0);
+    }
+
+    #[test]
+    fn replays_recorded_game() {
+        let mut arcade = Arcade::new(Computer::initialize(&tiny_game())).recording();
+        arcade.play(&mut AutoPilot, None).unwrap();
+        let session = arcade.session().unwrap().clone();
+        assert_eq!(session.inputs().collect::<Vec<_>>(), vec![1]);
+        assert_eq!(session.replay(&mut Computer::initialize(&tiny_game())), Ok(()));
+
+        let mut saved = session.to_string();
+        saved = saved.replace("in 1", "in -1");
+        let tampered = Session::parse(&saved).unwrap();
+        let divergence = tampered.replay(&mut Computer::initialize(&tiny_game())).unwrap_err();
+        assert_eq!(divergence.step, 13);
+        assert_eq!(divergence.to_string(), "Diverged at step 13: expected `out 2`, found `out 0`");
+    }
+}
@@ -0,0 +1,136 @@
+use crate::intcode_computer::{self, Computer, IterInput};
+use crate::parse::ParseError;
+
+const SHIP_SIZE: i64 = 100;
+const MAX_SLOPE: i64 = 10;
+
+#[aoc_generator(day19)]
+fn parse(input: &str) -> Result<Vec<i64>, ParseError> {
+    intcode_computer::parse_program(input)
+}
+
+#[aoc(day19, part1)]
+fn part1(program: &[i64]) -> usize {
+    let beam = TractorBeam { program };
+    (0..50)
+        .flat_map(|y| (0..50).map(move |x| (x, y)))
+        .filter(|(x, y)| beam.is_pulled(*x, *y))
+        .count()
+}
+
+#[aoc(day19, part2)]
+fn part2(program: &[i64]) -> i64 {
+    let beam = TractorBeam { program };
+    let (x, y) = beam.find_square(SHIP_SIZE);
+    x * 10000 + y
+}
+
+struct TractorBeam<'a> {
+    program: &'a [i64],
+}
+
+impl<'a> TractorBeam<'a> {
+    /// Deploys a drone to `(x, y)`. The drone program halts after one answer, so every query
+    /// needs a fresh computer.
+    fn is_pulled(&self, x: i64, y: i64) -> bool {
+        if x < 0 || y < 0 {
+            return false;
+        }
+        let mut computer = Computer::initialize(self.program);
+        let mut pulled = false;
+        computer.run_with_io(&mut IterInput::new(vec![x, y]), &mut |o| pulled = o == 1);
+        pulled
+    }
+
+    /// Leftmost beam point in row `y` at or after `from`. Rows close to the emitter can be
+    /// empty, so this gives up after `MAX_SLOPE` columns per row.
+    fn left_edge(&self, y: i64, from: i64) -> Option<i64> {
+        (from..=from + MAX_SLOPE * (y + 1)).find(|x| self.is_pulled(*x, y))
+    }
+
+    /// Top left corner of the closest `size` by `size` square that fits in the beam.
+    ///
+    /// Follows the beam's left edge down row by row. The square's bottom left corner sits on
+    /// that edge, so the square fits once its top right corner is also in the beam.
+    fn find_square(&self, size: i64) -> (i64, i64) {
+        let mut x = 0;
+        let mut y = size - 1;
+        loop {
+            if let Some(edge) = self.left_edge(y, x) {
+                x = edge;
+                if self.is_pulled(x + size - 1, y - (size - 1)) {
+                    return (x, y - (size - 1));
+                }
+            }
+            y += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A drone program for a beam covering every point with x <= y <= 2x.
+    const BEAM: &str = "3,100,3,101,7,101,100,102,1002,100,2,103,7,103,101,104,1,102,104,105,1008,105,0,105,4,105,99";
+
+    fn brute_force_square(beam: &TractorBeam, size: i64) -> (i64, i64) {
+        (0..)
+            .flat_map(|y| (0..=y).map(move |x| (x, y)))
+            .find(|(x, y)| (0..size).all(|dy| (0..size).all(|dx| beam.is_pulled(x + dx, y + dy))))
+            .unwrap()
+    }
+
+    #[test]
+    fn scans_beam() {
+        let program = parse(BEAM).unwrap();
+        let beam = TractorBeam { program: &program };
+        assert!(beam.is_pulled(0, 0));
+        assert!(beam.is_pulled(3, 5));
+        assert!(!beam.is_pulled(3, 7));
+        assert!(!beam.is_pulled(5, 3));
+        assert!(!beam.is_pulled(-1, 0));
+        assert_eq!(beam.left_edge(9, 0), Some(5));
+    }
+
+    #[test]
+    fn finds_closest_square() {
+        let program = parse(BEAM).unwrap();
+        let beam = TractorBeam { program: &program };
+        for size in 1..=5 {
+            assert_eq!(beam.find_square(size), brute_force_square(&beam, size), "size {}", size);
+        }
+    }
+
+    /// A drone program for a beam covering every point with `low * x <= y <= high * x`,
+    /// with each slope given as a fraction.
+    fn sloped_beam(low: (i64, i64), high: (i64, i64)) -> Vec<i64> {
+        vec![
+            3, 100, 3, 101,
+            1002, 100, low.0, 102, 1002, 101, low.1, 103, 7, 103, 102, 104,
+            1002, 100, high.0, 105, 1002, 101, high.1, 106, 7, 105, 106, 107,
+            1, 104, 107, 108, 1008, 108, 0, 108, 4, 108, 99,
+        ]
+    }
+
+    #[test]
+    fn counts_points_in_area() {
+        let program = parse(BEAM).unwrap();
+        let expected = (0..50).flat_map(|y| (0..50).map(move |x| (x, y))).filter(|(x, y)| x <= y && *y <= 2 * x).count();
+        assert_eq!(part1(&program), expected);
+    }
+
+    #[test]
+    fn finds_square_past_empty_rows() {
+        // Rows 1, 3 and 5 are empty.
+        let program = sloped_beam((7, 4), (2, 1));
+        let beam = TractorBeam { program: &program };
+        assert!(beam.is_pulled(3, 6));
+        assert!(!beam.is_pulled(2, 5));
+        assert_eq!(beam.left_edge(5, 0), None);
+        assert_eq!(beam.left_edge(6, 0), Some(3));
+        for size in 1..=4 {
+            assert_eq!(beam.find_square(size), brute_force_square(&beam, size), "size {}", size);
+        }
+    }
+}
@@ -1,37 +1,88 @@
-use crate::intcode_computer::{self, Computer};
+use crate::intcode_computer::{self, Computer, ParameterSearch, ParameterSpace, Patch};
+use crate::parse::ParseError;
 
 const TARGET: i64 = 19690720;
 
 #[aoc_generator(day2)]
-fn parse(input: &str) -> Vec<i64> {
-    intcode_computer::parse_program(input)
+fn parse(input: &str) -> Result<Vec<i64>, ParseError> {
+    let program = intcode_computer::parse_program(input)?;
+    if program.len() < 3 {
+        return Err(ParseError::at_end(input.trim_end(), "Program is too short to take a noun and verb at addresses 1 and 2"));
+    }
+    Ok(program)
 }
 
 #[aoc(day2, part1)]
 fn part1(ints: &[i64]) -> i64 {
-    let mut alarm_program = ints.to_vec();
-    alarm_program[1] = 12;
-    alarm_program[2] = 2;
-
-    let mut computer = Computer::initialize(&alarm_program);
+    let alarm = input_patch(12, 2);
+    let mut computer = Computer::initialize_patched(ints, &[&alarm]).expect("Program too short!");
     computer.run();
     *computer.access(0)
 }
 
 #[aoc(day2, part2)]
 fn part2(program: &[i64]) -> i64 {
-    let size = program.len();
-    for noun in 0..size {
-        for verb in 0..size {
-            let mut program = program.to_vec();
-            program[1] = noun as i64;
-            program[2] = verb as i64;
-            let mut computer = Computer::initialize(&program);
+    let values: Vec<i64> = (0..program.len() as i64).collect();
+    let space = ParameterSpace::product(&[values.clone(), values]);
+    let search = ParameterSearch::new(
+        program,
+        space,
+        |program, candidate| {
+            let patch = input_patch(candidate[0], candidate[1]);
+            Computer::initialize_patched(program, &[&patch]).expect("Program too short!")
+        },
+        |mut computer| {
             computer.run();
-            if *computer.access(0) == TARGET {
-                return (100 * noun + verb) as i64
-            }
-        }
+            *computer.access(0)
+        },
+    );
+
+    let (candidate, _) = search.find_first(|output| *output == TARGET).expect("No noun and verb found!");
+    100 * candidate[0] + candidate[1]
+}
+
+fn input_patch(noun: i64, verb: i64) -> Patch {
+    Patch::named("input").set(1, noun).set(2, verb)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(program: &str) -> Vec<i64> {
+        let mut computer = Computer::initialize(&parse(program).unwrap());
+        computer.run();
+        computer.memory().to_vec()
+    }
+
+    #[test]
+    fn runs_examples() {
+        assert_eq!(run("1,9,10,3,2,3,11,0,99,30,40,50"), vec![3500, 9, 10, 70, 2, 3, 11, 0, 99, 30, 40, 50]);
+        assert_eq!(run("1,0,0,0,99"), vec![2, 0, 0, 0, 99]);
+        assert_eq!(run("2,3,0,3,99"), vec![2, 3, 0, 6, 99]);
+        assert_eq!(run("2,4,4,5,99,0"), vec![2, 4, 4, 5, 99, 9801]);
+        assert_eq!(run("1,1,1,4,99,5,6,0,99"), vec![30, 1, 1, 4, 2, 5, 6, 0, 99]);
+    }
+
+    #[test]
+    fn rejects_programs_without_noun_and_verb() {
+        assert_eq!(
+            parse("1,2\n"),
+            Err(ParseError::new(1, 4, "", "Program is too short to take a noun and verb at addresses 1 and 2")));
+        assert!(parse("1,2,3").is_ok());
+    }
+
+    #[test]
+    fn patches_noun_and_verb() {
+        let mut computer = Computer::initialize_patched(&parse("1,0,0,0,99").unwrap(), &[&input_patch(4, 4)]).unwrap();
+        computer.run();
+        assert_eq!(*computer.access(0), 198);
+    }
+
+    #[test]
+    fn reports_invalid_values() {
+        assert_eq!(parse("1,0, 0,0,99\n").unwrap(), vec![1, 0, 0, 0, 99]);
+        assert_eq!(parse("1,0,,0,99").unwrap_err(), ParseError::new(1, 5, "", "Invalid number"));
+        assert_eq!(parse("1,0,0,O,99").unwrap_err(), ParseError::new(1, 7, "O", "Invalid number"));
     }
-    panic!()
 }
@@ -1,30 +1,219 @@
 use std::collections::HashMap;
 use std::collections::HashSet;
 
+use crate::grid::{BoundingBox, DenseGrid, Direction, Point};
+use crate::parse::{self, ParseError};
+use crate::search;
+
 #[aoc_generator(day20)]
-fn parse(input: &str) -> i32 {
-    let passages: HashSet<Coordinate> = 
-        input.lines().enumerate()
-            .flat_map(|(y, l)| l.chars().enumerate().filter(|(_, c)| *c == '.').map(move |(x, _)| (x, y)))
-            .map(|(x, y)| Coordinate(x, y))
-            .collect();
+fn parse(input: &str) -> Result<Maze, ParseError> {
+    parse::check_chars(input, |c| " #.".contains(c) || c.is_ascii_uppercase(), "Expected ' ', '#', '.' or a label letter")?;
+    let grid = DenseGrid::parse(input, |c| c);
+    let error_at = |p: Point<usize>, label: &str, message: &str| ParseError::new(p.y + 1, p.x + 1, label, message);
+    let at = |p: Option<Point<usize>>| *p.and_then(|p| grid.get(&p)).unwrap_or(&' ');
+
+    let passages: HashSet<Point<usize>> =
+        grid.iter().filter(|(_, c)| **c == '.').map(|(p, _)| p).collect();
+
+    let walls: Vec<Point<usize>> = grid.iter().filter(|(_, c)| **c == '#').map(|(p, _)| p).collect();
+    let bounds = BoundingBox::of(&walls).ok_or_else(|| ParseError::at_end(input, "No walls"))?;
+
+    // Labels read left to right or top to bottom, and sit next to the passage they lead from.
+    let mut entrances: HashMap<String, Vec<Entrance>> = HashMap::new();
+    for passage in &passages {
+        for direction in Direction::ALL.iter() {
+            let near = passage.checked_advance(*direction, 1);
+            let far = passage.checked_advance(*direction, 2);
+            let (first, second) = match direction {
+                Direction::Up | Direction::Left => (far, near),
+                Direction::Down | Direction::Right => (near, far),
+            };
+            match (first, at(first), at(second)) {
+                (Some(start), a, b) if a.is_ascii_uppercase() && b.is_ascii_uppercase() =>
+                    entrances.entry([a, b].iter().collect()).or_default().push(Entrance { passage: *passage, label: start }),
+                _ => {},
+            }
+        }
+    }
+    let mut entrances: Vec<_> = entrances.into_iter().collect();
+    entrances.sort();
+    for (_, coordinates) in &mut entrances {
+        coordinates.sort_by_key(|e| (e.label.y, e.label.x));
+    }
+
+    let mut start = None;
+    let mut end = None;
+    let mut portals = HashMap::new();
+    let mut outer_portals = HashSet::new();
+    for (label, coordinates) in entrances {
+        match (label.as_str(), coordinates.as_slice()) {
+            ("AA", [c]) => start = Some(c.passage),
+            ("ZZ", [c]) => end = Some(c.passage),
+            (_, [a, b]) => {
+                let portal =
+                    if bounds.is_edge(&a.passage) {
+                        Portal { inner: b.passage, outer: a.passage }
+                    } else if bounds.is_edge(&b.passage) {
+                        Portal { inner: a.passage, outer: b.passage }
+                    } else {
+                        return Err(error_at(a.label, &label, "Portal has no outer entrance"));
+                    };
+                portals.insert(portal.inner, portal.outer);
+                portals.insert(portal.outer, portal.inner);
+                outer_portals.insert(portal.outer);
+            },
+            _ => return Err(error_at(coordinates[0].label, &label, "Portal must have exactly two entrances")),
+        }
+    }
+
+    Ok(Maze {
+        passages,
+        start: start.ok_or_else(|| ParseError::at_end(input, "No start (AA)"))?,
+        end: end.ok_or_else(|| ParseError::at_end(input, "No end (ZZ)"))?,
+        portals,
+        outer_portals,
+    })
+}
 
-        1
+/// A passage next to a portal label, and where that label starts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Entrance {
+    passage: Point<usize>,
+    label: Point<usize>,
 }
 
 #[aoc(day20, part1)]
-fn part1(a: &i32) -> i32 {
-    1
+fn part1(maze: &Maze) -> usize {
+    maze.shortest_path(false).expect("No path through the maze!")
 }
 
-#[derive(Debug, PartialEq, Eq, Hash)]
-struct Coordinate(usize, usize);
+#[aoc(day20, part2)]
+fn part2(maze: &Maze) -> usize {
+    maze.shortest_path(true).expect("No path through the recursive maze!")
+}
 
-struct Portal(Coordinate, Coordinate);
+struct Portal {
+    inner: Point<usize>,
+    outer: Point<usize>,
+}
 
 struct Maze {
-    passages: HashSet<Coordinate>,
-    start: Coordinate,
-    end: Coordinate,
-    portals: HashMap<Coordinate, Coordinate>,
-}
\ No newline at end of file
+    passages: HashSet<Point<usize>>,
+    start: Point<usize>,
+    end: Point<usize>,
+    portals: HashMap<Point<usize>, Point<usize>>,
+    outer_portals: HashSet<Point<usize>>,
+}
+
+impl Maze {
+    /// Steps from `start` to `end`. When `recursive`, inner portals lead one level deeper and
+    /// outer portals one level up; outer portals are walls on the outermost level, and the
+    /// exit only counts there.
+    fn shortest_path(&self, recursive: bool) -> Option<usize> {
+        // A shortest path never goes deeper than (portal pairs)². On a path that does, two
+        // levels are entered by the same inner portal and left by the same outer portal, and
+        // replacing the stay in the shallower level with the stay in the deeper one, shifted
+        // up, gives a shorter path. Levels below 0 all look alike, so the shift keeps it valid.
+        let pairs = self.portals.len() / 2;
+        let max_level = if recursive { pairs * pairs } else { 0 };
+
+        let neighbors = |&(position, level): &(Point<usize>, usize)| {
+            let mut next: Vec<(Point<usize>, usize)> =
+                position.neighbors().into_iter()
+                    .filter(|n| self.passages.contains(n))
+                    .map(|n| (n, level))
+                    .collect();
+            if let Some(destination) = self.portals.get(&position) {
+                let outer = self.outer_portals.contains(&position);
+                if !recursive {
+                    next.push((*destination, level));
+                } else if outer && level > 0 {
+                    next.push((*destination, level - 1));
+                } else if !outer && level < max_level {
+                    next.push((*destination, level + 1));
+                }
+            }
+            next
+        };
+
+        search::bfs((self.start, 0), neighbors, |state| *state == (self.end, 0)).map(|path| path.cost)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "         A           
+         A           
+  #######.#########  
+  #######.........#  
+  #######.#######.#  
+  #######.#######.#  
+  #######.#######.#  
+  #####  B    ###.#  
+BC...##  C    ###.#  
+  ##.##       ###.#  
+  ##...DE  F  ###.#  
+  #####    G  ###.#  
+  #########.#####.#  
+DE..#######...###.#  
+  #.#########.###.#  
+FG..#########.....#  
+  ###########.#####  
+             Z       
+             Z       ";
+
+    #[test]
+    fn shortest_path_through_portals() {
+        assert_eq!(part1(&parse(EXAMPLE).unwrap()), 23);
+    }
+
+    #[test]
+    fn shortest_path_through_levels() {
+        assert_eq!(part2(&parse(EXAMPLE).unwrap()), 26);
+    }
+
+    #[test]
+    fn reports_bad_portals_at_their_labels() {
+        let unpaired = EXAMPLE.replace("FG..#", "XY..#");
+        assert_eq!(
+            parse(&unpaired).err(),
+            Some(ParseError::new(11, 12, "FG", "Portal must have exactly two entrances")));
+
+        let inner = EXAMPLE.replace("BC...##  C", "  ...##  C").replace("  ##...DE", "  ##.#BC.");
+        assert_eq!(
+            parse(&inner).err(),
+            Some(ParseError::new(8, 10, "BC", "Portal has no outer entrance")));
+    }
+
+    /// Three corridors: the first leads in through an inner portal, the second back out
+    /// through an outer one, and the third to the exit.
+    fn stairs() -> Maze {
+        let row = |y| (0..3).map(move |x| Point::new(x, y));
+        let (inner, outer) = (Point::new(2, 0), Point::new(2, 2));
+        Maze {
+            passages: row(0).chain(row(2)).chain(row(4)).collect(),
+            start: Point::new(0, 0),
+            end: Point::new(2, 4),
+            portals: [(inner, Point::new(0, 2)), (Point::new(0, 2), inner), (outer, Point::new(0, 4)), (Point::new(0, 4), outer)]
+                .iter().cloned().collect(),
+            outer_portals: [Point::new(0, 2), outer].iter().cloned().collect(),
+        }
+    }
+
+    #[test]
+    fn recursive_path_returns_to_the_top_level() {
+        assert_eq!(stairs().shortest_path(false), Some(8));
+        assert_eq!(stairs().shortest_path(true), Some(8));
+    }
+
+    #[test]
+    fn unreachable_exit_ends_the_search() {
+        // The middle corridor only has outer portals, which are walls on the top level.
+        let mut maze = stairs();
+        maze.start = Point::new(0, 2);
+        assert_eq!(maze.shortest_path(false), Some(5));
+        assert_eq!(maze.shortest_path(true), None);
+    }
+}
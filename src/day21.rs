@@ -0,0 +1,455 @@
+use itertools::Itertools;
+use std::fmt;
+
+use crate::intcode_computer::{self, Computer, IterInput};
+use crate::parse::ParseError;
+
+const MAX_INSTRUCTIONS: usize = 15;
+
+#[aoc_generator(day21)]
+fn parse(input: &str) -> Result<Vec<i64>, ParseError> {
+    intcode_computer::parse_program(input)
+}
+
+/// Jumps if any of the next three tiles is a hole and the landing tile is ground.
+const WALK_SCRIPT: &str = "\
+NOT A J
+NOT B T
+OR T J
+NOT C T
+OR T J
+AND D J";
+
+/// As when walking, but only if the droid can step or jump again after landing.
+const RUN_SCRIPT: &str = "\
+NOT A J
+NOT B T
+OR T J
+NOT C T
+OR T J
+AND D J
+NOT E T
+NOT T T
+OR H T
+AND T J";
+
+#[aoc(day21, part1)]
+fn part1(program: &[i64]) -> i64 {
+    survey(program, WALK_SCRIPT, Mode::Walk)
+}
+
+#[aoc(day21, part2)]
+fn part2(program: &[i64]) -> i64 {
+    survey(program, RUN_SCRIPT, Mode::Run)
+}
+
+/// Hull damage reported by the droid running `script`, or by the first candidate from the
+/// search if `script` doesn't get it across.
+fn survey(program: &[i64], script: &str, mode: Mode) -> i64 {
+    let droid = Springdroid { program };
+    let script = Springscript::parse(script).unwrap_or_else(|e| panic!("{}", e));
+    if let Ok(Outcome::Survived(damage)) = droid.run(&script, mode) {
+        return damage;
+    }
+    match droid.search(mode) {
+        Ok((_, damage)) => damage,
+        Err(report) => panic!("No springscript survived in {} mode!\n{}", mode, report),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Register {
+    A, B, C, D, E, F, G, H, I, T, J
+}
+
+impl Register {
+    fn parse(name: &str) -> Option<Register> {
+        match name {
+            "A" => Some(Register::A),
+            "B" => Some(Register::B),
+            "C" => Some(Register::C),
+            "D" => Some(Register::D),
+            "E" => Some(Register::E),
+            "F" => Some(Register::F),
+            "G" => Some(Register::G),
+            "H" => Some(Register::H),
+            "I" => Some(Register::I),
+            "T" => Some(Register::T),
+            "J" => Some(Register::J),
+            _ => None,
+        }
+    }
+
+    fn is_writable(self) -> bool {
+        self == Register::T || self == Register::J
+    }
+
+    /// Sensor registers, in order of distance from the droid.
+    fn sensors(mode: Mode) -> &'static [Register] {
+        match mode {
+            Mode::Walk => &[Register::A, Register::B, Register::C, Register::D],
+            Mode::Run => &[
+                Register::A, Register::B, Register::C, Register::D, Register::E,
+                Register::F, Register::G, Register::H, Register::I,
+            ],
+        }
+    }
+}
+
+impl fmt::Display for Register {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operation {
+    And, Or, Not
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Instruction {
+    operation: Operation,
+    source: Register,
+    destination: Register,
+}
+
+impl Instruction {
+    fn new(operation: Operation, source: Register, destination: Register) -> Instruction {
+        Instruction { operation, source, destination }
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let operation = match self.operation {
+            Operation::And => "AND",
+            Operation::Or => "OR",
+            Operation::Not => "NOT",
+        };
+        write!(f, "{} {} {}", operation, self.source, self.destination)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Walk, Run
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Mode::Walk => write!(f, "WALK"),
+            Mode::Run => write!(f, "RUN"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum SpringscriptError {
+    Syntax(String),
+    TooLong(usize),
+    UnavailableRegister(Register),
+    ReadOnlyRegister(Register),
+}
+
+impl fmt::Display for SpringscriptError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SpringscriptError::Syntax(line) => write!(f, "Invalid instruction: {}", line),
+            SpringscriptError::TooLong(len) =>
+                write!(f, "{} instructions is more than the droid's limit of {}", len, MAX_INSTRUCTIONS),
+            SpringscriptError::UnavailableRegister(r) => write!(f, "Register {} is not available in this mode", r),
+            SpringscriptError::ReadOnlyRegister(r) => write!(f, "Register {} cannot be written", r),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Springscript {
+    instructions: Vec<Instruction>,
+}
+
+impl Springscript {
+    fn parse(input: &str) -> Result<Springscript, SpringscriptError> {
+        let instructions =
+            input.lines()
+                .map(|l| l.trim())
+                .filter(|l| !l.is_empty())
+                .map(|l| {
+                    let parts: Vec<&str> = l.split_whitespace().collect();
+                    let operation = match parts.first() {
+                        Some(&"AND") => Operation::And,
+                        Some(&"OR") => Operation::Or,
+                        Some(&"NOT") => Operation::Not,
+                        _ => return Err(SpringscriptError::Syntax(l.to_string())),
+                    };
+                    match parts[1..] {
+                        [source, destination] => match (Register::parse(source), Register::parse(destination)) {
+                            (Some(source), Some(destination)) => Ok(Instruction::new(operation, source, destination)),
+                            _ => Err(SpringscriptError::Syntax(l.to_string())),
+                        },
+                        _ => Err(SpringscriptError::Syntax(l.to_string())),
+                    }
+                })
+                .collect::<Result<_, _>>()?;
+        Ok(Springscript { instructions })
+    }
+
+    /// Checks that the droid will accept the program in `mode`.
+    fn verify(&self, mode: Mode) -> Result<(), SpringscriptError> {
+        if self.instructions.len() > MAX_INSTRUCTIONS {
+            return Err(SpringscriptError::TooLong(self.instructions.len()));
+        }
+        let sensors = Register::sensors(mode);
+        for instruction in &self.instructions {
+            if !instruction.source.is_writable() && !sensors.contains(&instruction.source) {
+                return Err(SpringscriptError::UnavailableRegister(instruction.source));
+            }
+            if !instruction.destination.is_writable() {
+                return Err(SpringscriptError::ReadOnlyRegister(instruction.destination));
+            }
+        }
+        Ok(())
+    }
+
+    /// The ASCII text the droid reads, ending with the command to start moving.
+    fn emit(&self, mode: Mode) -> String {
+        let mut text = String::new();
+        for instruction in &self.instructions {
+            text.push_str(&format!("{}\n", instruction));
+        }
+        text.push_str(&format!("{}\n", mode));
+        text
+    }
+
+    /// Jumps when any of `holes` is a hole and `D` is ground, so the droid lands safely. If
+    /// `escapes` is non-empty, also requires one of them to be ground so the droid can keep
+    /// going after landing.
+    fn jump_over(holes: &[Register], escapes: &[Register]) -> Springscript {
+        let mut instructions = vec![];
+        for (i, hole) in holes.iter().enumerate() {
+            if i == 0 {
+                instructions.push(Instruction::new(Operation::Not, *hole, Register::J));
+            } else {
+                instructions.push(Instruction::new(Operation::Not, *hole, Register::T));
+                instructions.push(Instruction::new(Operation::Or, Register::T, Register::J));
+            }
+        }
+        instructions.push(Instruction::new(Operation::And, Register::D, Register::J));
+
+        if !escapes.is_empty() {
+            instructions.push(Instruction::new(Operation::Not, escapes[0], Register::T));
+            instructions.push(Instruction::new(Operation::Not, Register::T, Register::T));
+            for escape in &escapes[1..] {
+                instructions.push(Instruction::new(Operation::Or, *escape, Register::T));
+            }
+            instructions.push(Instruction::new(Operation::And, Register::T, Register::J));
+        }
+        Springscript { instructions }
+    }
+
+    /// Programs to try in `mode`, shortest first.
+    fn candidates(mode: Mode) -> Vec<Springscript> {
+        let near = [Register::A, Register::B, Register::C];
+        let far: Vec<Register> =
+            Register::sensors(mode).iter().cloned().filter(|r| *r > Register::D).collect();
+
+        let mut candidates: Vec<Springscript> =
+            (1..=near.len())
+                .flat_map(|n| near.iter().cloned().combinations(n))
+                .flat_map(|holes| {
+                    let escapes = (1..=2).flat_map(|n| far.iter().cloned().combinations(n));
+                    std::iter::once(vec![]).chain(escapes)
+                        .map(|escapes| Springscript::jump_over(&holes, &escapes))
+                        .collect::<Vec<_>>()
+                })
+                .filter(|s| s.verify(mode).is_ok())
+                .collect();
+        candidates.sort_by_key(|s| s.instructions.len());
+        candidates
+    }
+}
+
+impl fmt::Display for Springscript {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for instruction in &self.instructions {
+            writeln!(f, "{}", instruction)?;
+        }
+        Ok(())
+    }
+}
+
+/// The droid's last moments, as drawn by the droid after falling into space.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct FallReport {
+    frames: Vec<Vec<String>>,
+}
+
+impl FallReport {
+    fn parse(text: &str) -> FallReport {
+        let animation = text.split("Didn't make it across:").nth(1).unwrap_or(text);
+        let frames =
+            animation.split("\n\n")
+                .map(|f| f.lines().filter(|l| !l.is_empty()).map(|l| l.to_string()).collect::<Vec<_>>())
+                .filter(|f| !f.is_empty())
+                .collect();
+        FallReport { frames }
+    }
+
+    /// The row of hull the droid was crossing.
+    fn hull(&self) -> Option<&str> {
+        self.frames.first().and_then(|f| f.last()).map(|l| l.as_str())
+    }
+
+    /// Column of the droid in the last frame it appears in.
+    fn fell_at(&self) -> Option<usize> {
+        self.frames.iter().rev().find_map(|f| f.iter().find_map(|l| l.find('@')))
+    }
+}
+
+impl fmt::Display for FallReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.hull(), self.fell_at()) {
+            (Some(hull), Some(column)) => {
+                writeln!(f, "{}", hull)?;
+                writeln!(f, "{}^ fell after {} frames", " ".repeat(column), self.frames.len())
+            },
+            _ => writeln!(f, "Droid fell; no animation was drawn."),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Outcome {
+    Survived(i64),
+    Fell(FallReport),
+}
+
+struct Springdroid<'a> {
+    program: &'a [i64],
+}
+
+impl<'a> Springdroid<'a> {
+    fn run(&self, script: &Springscript, mode: Mode) -> Result<Outcome, SpringscriptError> {
+        script.verify(mode)?;
+
+        let mut computer = Computer::initialize(self.program);
+        let text = script.emit(mode);
+        let mut input = IterInput::new(text.bytes().map(|b| b as i64));
+        let mut output = vec![];
+        computer.run_with_io(&mut input, &mut output);
+
+        match output.last() {
+            Some(damage) if *damage > 127 => Ok(Outcome::Survived(*damage)),
+            _ => {
+                let text: String = output.iter().map(|c| *c as u8 as char).collect();
+                Ok(Outcome::Fell(FallReport::parse(&text)))
+            },
+        }
+    }
+
+    /// Tries candidate programs until one gets the droid across, returning it along with the
+    /// hull damage it reports. If none do, returns how the last candidate fell.
+    fn search(&self, mode: Mode) -> Result<(Springscript, i64), FallReport> {
+        let mut last_fall = FallReport { frames: vec![] };
+        for script in Springscript::candidates(mode) {
+            match self.run(&script, mode) {
+                Ok(Outcome::Survived(damage)) => return Ok((script, damage)),
+                Ok(Outcome::Fell(report)) => last_fall = report,
+                Err(_) => {},
+            }
+        }
+        Err(last_fall)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "NOT A J\nNOT B T\nAND T J\nNOT C T\nAND T J\nAND D J";
+
+    #[test]
+    fn parses_and_emits() {
+        let script = Springscript::parse(EXAMPLE).unwrap();
+        assert_eq!(script.instructions.len(), 6);
+        assert_eq!(script.emit(Mode::Walk), format!("{}\nWALK\n", EXAMPLE));
+        assert_eq!(Springscript::parse("JUMP A J"), Err(SpringscriptError::Syntax("JUMP A J".to_string())));
+        assert_eq!(Springscript::parse("NOT A"), Err(SpringscriptError::Syntax("NOT A".to_string())));
+    }
+
+    #[test]
+    fn verifies_registers() {
+        assert_eq!(Springscript::parse("NOT E J").unwrap().verify(Mode::Walk), Err(SpringscriptError::UnavailableRegister(Register::E)));
+        assert_eq!(Springscript::parse("NOT E J").unwrap().verify(Mode::Run), Ok(()));
+        assert_eq!(Springscript::parse("NOT J A").unwrap().verify(Mode::Run), Err(SpringscriptError::ReadOnlyRegister(Register::A)));
+        let too_long = "NOT A J\n".repeat(MAX_INSTRUCTIONS + 1);
+        assert_eq!(Springscript::parse(&too_long).unwrap().verify(Mode::Walk), Err(SpringscriptError::TooLong(16)));
+    }
+
+    #[test]
+    fn jumps_over_holes() {
+        let script = Springscript::jump_over(&[Register::A, Register::C], &[Register::E, Register::H]);
+        assert_eq!(
+            script.to_string(),
+            "NOT A J\nNOT C T\nOR T J\nAND D J\nNOT E T\nNOT T T\nOR H T\nAND T J\n");
+        assert!(Springscript::candidates(Mode::Walk).iter().all(|s| s.verify(Mode::Walk).is_ok()));
+    }
+
+    #[test]
+    fn scripts_are_search_candidates() {
+        let (a, b, c) = (Register::A, Register::B, Register::C);
+        let walk = Springscript::parse(WALK_SCRIPT).unwrap();
+        assert_eq!(walk, Springscript::jump_over(&[a, b, c], &[]));
+        assert!(Springscript::candidates(Mode::Walk).contains(&walk));
+
+        let run = Springscript::parse(RUN_SCRIPT).unwrap();
+        assert_eq!(run, Springscript::jump_over(&[a, b, c], &[Register::E, Register::H]));
+        assert!(Springscript::candidates(Mode::Run).contains(&run));
+        assert_eq!(run.verify(Mode::Walk), Err(SpringscriptError::UnavailableRegister(Register::E)));
+    }
+
+    /// A stand-in for the droid that gets across only if the first instruction of the script
+    /// reads `register`, and otherwise draws itself falling into a hole.
+    fn fake_droid(register: char) -> Vec<i64> {
+        let mut program = vec![3, 100, 3, 100, 3, 100, 3, 100, 3, 100];
+        program.extend(&[1008, 100, register as i64, 101, 1005, 101, 26]);
+        program.extend(&[104, '#' as i64, 104, '@' as i64, 104, '#' as i64, 104, '\n' as i64, 99]);
+        program.extend(&[104, 1234, 99]);
+        program
+    }
+
+    #[test]
+    fn searches_for_surviving_script() {
+        let program = fake_droid('C');
+        let droid = Springdroid { program: &program };
+        for &mode in &[Mode::Walk, Mode::Run] {
+            let (script, damage) = droid.search(mode).unwrap();
+            assert_eq!(damage, 1234);
+            assert_eq!(script.instructions[0].source, Register::C);
+            assert_eq!(script.verify(mode), Ok(()));
+        }
+    }
+
+    #[test]
+    fn search_reports_last_fall() {
+        let program = fake_droid('J');
+        let droid = Springdroid { program: &program };
+        let report = droid.search(Mode::Walk).unwrap_err();
+        assert_eq!(report.hull(), Some("#@#"));
+        assert_eq!(report.fell_at(), Some(1));
+    }
+
+    #[test]
+    fn reports_fall() {
+        let text = "Input instructions:\n\nWalking...\n\nDidn't make it across:\n\n\
+                    .................\n.................\n@................\n#####.###########\n\n\
+                    .................\n.................\n.................\n####@.###########\n\n\
+                    .................\n.................\n.................\n#####@###########\n";
+        let report = FallReport::parse(text);
+        assert_eq!(report.frames.len(), 3);
+        assert_eq!(report.hull(), Some("#####.###########"));
+        assert_eq!(report.fell_at(), Some(5));
+    }
+}
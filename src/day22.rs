@@ -1,27 +1,32 @@
 use regex::Regex;
 
+use crate::parse::{self, parse_number, ParseError};
+
 #[aoc_generator(day22)]
-fn parse(input: &str) -> Vec<Technique> {
-    let deal_new_stack_regex = Regex::new(r"deal into new stack").unwrap();
-    let cut_regex = Regex::new(r"cut (-?\d+)").unwrap();
-    let deal_increment_regex = Regex::new(r"deal with increment (\d+)").unwrap();
+fn parse(input: &str) -> Result<Vec<Technique>, ParseError> {
+    let deal_new_stack_regex = Regex::new(r"^deal into new stack$").unwrap();
+    let cut_regex = Regex::new(r"^cut (-?\d+)$").unwrap();
+    let deal_increment_regex = Regex::new(r"^deal with increment (\d+)$").unwrap();
 
-    input.lines().map(|l| {
+    parse::lines(input).map(|l| {
         if deal_new_stack_regex.is_match(l) {
-            Technique::DealNewStack
-        } else if cut_regex.is_match(l) {
-            let n = cut_regex.captures(l).unwrap()[1].parse().unwrap();
-            Technique::Cut(n)
-        } else if deal_increment_regex.is_match(l) {
-            let n = deal_increment_regex.captures(l).unwrap()[1].parse().unwrap();
-            Technique::DealIncrement(n)
+            Ok(Technique::DealNewStack)
+        } else if let Some(c) = cut_regex.captures(l) {
+            Ok(Technique::Cut(parse_number(input, c.get(1).unwrap().as_str())?))
+        } else if let Some(c) = deal_increment_regex.captures(l) {
+            let n = c.get(1).unwrap().as_str();
+            match parse_number(input, n)? {
+                0 => Err(ParseError::at(input, n, "Increment must be positive")),
+                n => Ok(Technique::DealIncrement(n)),
+            }
         } else {
-            panic!("Invalid technique!");
+            Err(ParseError::at(input, l, "Invalid technique"))
         }
     })
     .collect()
 }
 
+#[derive(Debug)]
 enum Technique {
     DealNewStack,
     Cut(i64),
@@ -29,6 +34,7 @@ enum Technique {
 }
 
 impl Technique {
+    #[cfg(test)]
     fn apply(&self, deck: &mut Vec<usize>) {
         match self {
             Technique::DealNewStack => deck.reverse(),
@@ -46,50 +52,166 @@ impl Technique {
                 let len = deck.len();
                 let mut new_deck = vec![0; len];
                 let n = *n as usize;
-                for i in 0..len {
-                    new_deck[target_index] = deck[i];
+                for card in deck.iter() {
+                    new_deck[target_index] = *card;
                     target_index = (target_index + n) % len;
                 }
                 *deck = new_deck;
             },
         }
     }
+
+    /// Where the technique moves each position, as a function of the old position.
+    fn as_function(&self, deck_size: i128) -> LinearFunction {
+        match self {
+            Technique::DealNewStack => LinearFunction::new(-1, -1, deck_size),
+            Technique::Cut(n) => LinearFunction::new(1, -(*n as i128), deck_size),
+            Technique::DealIncrement(n) => LinearFunction::new(*n as i128, 0, deck_size),
+        }
+    }
+}
+
+/// `a * x + b` modulo `n`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct LinearFunction {
+    a: i128,
+    b: i128,
+    n: i128,
+}
+
+impl LinearFunction {
+    fn new(a: i128, b: i128, n: i128) -> LinearFunction {
+        LinearFunction { a: a.rem_euclid(n), b: b.rem_euclid(n), n }
+    }
+
+    fn identity(n: i128) -> LinearFunction {
+        LinearFunction::new(1, 0, n)
+    }
+
+    fn apply(&self, x: i128) -> i128 {
+        (self.a * x + self.b).rem_euclid(self.n)
+    }
+
+    /// The function that applies `self`, then `next`.
+    fn then(&self, next: &LinearFunction) -> LinearFunction {
+        LinearFunction::new(next.a * self.a, next.a * self.b + next.b, self.n)
+    }
+
+    /// `self` applied `times` times, by repeated squaring.
+    fn repeat(&self, mut times: u64) -> LinearFunction {
+        let mut result = LinearFunction::identity(self.n);
+        let mut power = *self;
+        while times > 0 {
+            if times % 2 == 1 {
+                result = result.then(&power);
+            }
+            power = power.then(&power);
+            times /= 2;
+        }
+        result
+    }
+
+    /// Requires `a` to be invertible modulo `n`, which holds when `n` is prime.
+    fn inverse(&self) -> LinearFunction {
+        let a_inverse = mod_inverse(self.a, self.n);
+        LinearFunction::new(a_inverse, -a_inverse * self.b, self.n)
+    }
+}
+
+fn mod_inverse(a: i128, n: i128) -> i128 {
+    let (mut old_r, mut r) = (a.rem_euclid(n), n);
+    let (mut old_s, mut s) = (1, 0);
+    while r != 0 {
+        let quotient = old_r / r;
+        let next_r = old_r - quotient * r;
+        old_r = r;
+        r = next_r;
+        let next_s = old_s - quotient * s;
+        old_s = s;
+        s = next_s;
+    }
+    if old_r != 1 {
+        panic!("{} has no inverse modulo {}!", a, n);
+    }
+    old_s.rem_euclid(n)
+}
+
+fn compose(techniques: &[Technique], deck_size: i128) -> LinearFunction {
+    techniques.iter()
+        .fold(LinearFunction::identity(deck_size), |acc, t| acc.then(&t.as_function(deck_size)))
 }
 
 #[aoc(day22, part1)]
-fn part1(techniques: &[Technique]) -> usize {
-    let mut deck = (0..10007).collect();
-    techniques.iter().for_each(|t| t.apply(&mut deck));
-    deck.iter().position(|c| *c == 2019).unwrap()
+fn part1(techniques: &[Technique]) -> i128 {
+    compose(techniques, 10007).apply(2019)
 }
 
 #[aoc(day22, part2)]
-fn part2(techniques: &[Technique]) -> usize {
-    let size: usize = 119315717514047;
-    // let size: usize = 10007;
-    let mut one_shuffle = vec![];
-    for i in 0..size {
-        one_shuffle.push(i);
+fn part2(techniques: &[Technique]) -> i128 {
+    let deck_size = 119_315_717_514_047;
+    let shuffles = 101_741_582_076_661;
+    compose(techniques, deck_size).repeat(shuffles).inverse().apply(2020)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn simulate(techniques: &[Technique], deck_size: usize) -> Vec<usize> {
+        let mut deck = (0..deck_size).collect();
+        techniques.iter().for_each(|t| t.apply(&mut deck));
+        deck
     }
-    // let mut one_shuffle = (0..size).collect();
-    println!("asf");
-    techniques.iter().for_each(|t| t.apply(&mut one_shuffle));
-
-    let mut deck = (0..size).collect();
-    let shuffles: usize = 101741582076661;
-    for i in 0..shuffles {
-        if i % 1_000 == 0 {
-            println!("{}", i);
+
+    #[test]
+    fn deck_orders() {
+        for (techniques, order) in &[
+            ("deal with increment 7\ndeal into new stack\ndeal into new stack", [0, 3, 6, 9, 2, 5, 8, 1, 4, 7]),
+            ("cut 6\ndeal with increment 7\ndeal into new stack", [3, 0, 7, 4, 1, 8, 5, 2, 9, 6]),
+            ("deal with increment 7\ndeal with increment 9\ncut -2", [6, 3, 0, 7, 4, 1, 8, 5, 2, 9]),
+            ("deal into new stack\ncut -2\ndeal with increment 7\ncut 8\ncut -4\ndeal with increment 7\ncut 3\n\
+              deal with increment 9\ndeal with increment 3\ncut -1", [9, 2, 5, 8, 1, 4, 7, 0, 3, 6]),
+        ] {
+            assert_eq!(simulate(&parse(techniques).unwrap(), 10), order.to_vec(), "{}", techniques);
+        }
+    }
+
+    #[test]
+    fn composition_matches_simulation() {
+        let techniques = parse("deal with increment 7\ndeal into new stack\ncut -2\ndeal with increment 9\ncut 6\ndeal into new stack").unwrap();
+        for deck_size in [11, 13, 10007].iter() {
+            let deck = simulate(&techniques, *deck_size);
+            let shuffle = compose(&techniques, *deck_size as i128);
+            for (position, card) in deck.iter().enumerate() {
+                assert_eq!(shuffle.apply(*card as i128), position as i128);
+            }
         }
-        quick_shuffle(&mut deck, &one_shuffle);
     }
-    deck[2020]
-}
 
-fn quick_shuffle(deck: &mut Vec<usize>, shuffle_result: &[usize]) {
-    let mut new_deck = vec![0; deck.len()];
-    for i in 0..deck.len() {
-        new_deck[i] = deck[shuffle_result[i]];
+    #[test]
+    fn repeated_inverse_matches_simulation() {
+        let techniques = parse("cut 3\ndeal with increment 3\ndeal into new stack").unwrap();
+        let deck_size = 11;
+        let mut deck: Vec<usize> = (0..deck_size).collect();
+        for _ in 0..5 {
+            techniques.iter().for_each(|t| t.apply(&mut deck));
+        }
+        let unshuffle = compose(&techniques, deck_size as i128).repeat(5).inverse();
+        for (position, card) in deck.iter().enumerate() {
+            assert_eq!(unshuffle.apply(position as i128), *card as i128);
+        }
     }
-    *deck = new_deck;
-}
\ No newline at end of file
+
+    #[test]
+    fn reports_invalid_techniques() {
+        assert_eq!(
+            parse("cut 6\ndeal with increment 7 cards").unwrap_err(),
+            ParseError::new(2, 1, "deal with increment 7 cards", "Invalid technique"));
+        assert_eq!(
+            parse("cut 99999999999999999999").unwrap_err(),
+            ParseError::new(1, 5, "99999999999999999999", "Invalid number"));
+        assert_eq!(
+            parse("deal with increment 0").unwrap_err(),
+            ParseError::new(1, 21, "0", "Increment must be positive"));
+    }
+}
@@ -1,9 +1,10 @@
 use std::collections::{HashSet, VecDeque};
 
-use crate::intcode_computer::{self, Computer};
+use crate::intcode_computer::{self, Computer, FrameDecoder};
+use crate::parse::ParseError;
 
 #[aoc_generator(day23)]
-fn parse(input: &str) -> Vec<i64> {
+fn parse(input: &str) -> Result<Vec<i64>, ParseError> {
     intcode_computer::parse_program(input)
 }
 
@@ -13,19 +14,39 @@ fn part1(program: &[i64]) -> i64 {
     run_until_nat(&mut network)
 }
 
-struct Network {
+struct Packet {
+    address: usize,
+    x: i64,
+    y: i64,
+}
+
+impl Packet {
+    fn decode(triple: &[i64]) -> Packet {
+        Packet {
+            address: triple[0] as usize,
+            x: triple[1],
+            y: triple[2],
+        }
+    }
+}
+
+type PacketDecoder = FrameDecoder<Packet, fn(&[i64]) -> Packet>;
+
+/// Fifty computers running the NIC program, passing packets to each other and the NAT at
+/// address 255. Public so the benchmarks can drive it.
+pub struct Network {
     inputs: Vec<VecDeque<i64>>,
-    outputs: Vec<VecDeque<i64>>,
+    outputs: Vec<PacketDecoder>,
     computers: Vec<Computer>,
     idle: Vec<bool>,
     nat: Option<(i64, i64)>,
 }
 
 impl Network {
-    fn initialize(program: &[i64]) -> Network {
+    pub fn initialize(program: &[i64]) -> Network {
         let mut inputs: Vec<VecDeque<i64>> = (0..50).map(|_| VecDeque::new()).collect();
-        let mut outputs: Vec<VecDeque<i64>> = (0..50).map(|_| VecDeque::new()).collect();
-        let mut computers: Vec<Computer> = (0..50).map(|_| Computer::initialize(program)).collect();
+        let outputs: Vec<PacketDecoder> = (0..50).map(|_| FrameDecoder::new(3, Packet::decode as fn(&[i64]) -> Packet)).collect();
+        let computers: Vec<Computer> = (0..50).map(|_| Computer::initialize(program)).collect();
         (0..50).for_each(|i| inputs[i].push_back(i as i64));
 
         Network {
@@ -37,13 +58,14 @@ impl Network {
         }
     }
 
-    fn is_idle(&self) -> bool {
+    /// Whether every computer had nothing to read or send on its last run.
+    pub fn is_idle(&self) -> bool {
         self.idle.iter().all(|status| *status)
     }
 
     fn run_computer(&mut self, i: usize) {
         let mut idle = true;
-        if self.inputs[i].len() == 0 {
+        if self.inputs[i].is_empty() {
             let mut input = VecDeque::new();
             input.push_back(-1);
             self.computers[i].run_with_io(&mut input, &mut self.outputs[i]);
@@ -52,32 +74,36 @@ impl Network {
             self.computers[i].run_with_io(&mut self.inputs[i], &mut self.outputs[i]);
         }
 
-        if self.outputs[i].len() > 0 {
+        if !self.outputs[i].is_empty() {
             idle = false;
         }
-        while self.outputs[i].len() >= 3 {
-            let packet: Vec<i64> = self.outputs[i].drain(0..3).collect();
-            let (address, packet) = (packet[0] as usize, (packet[1], packet[2]));
-            if address == 255 {
-                self.nat = Some(packet);
+        while let Some(packet) = self.outputs[i].next_frame() {
+            if packet.address == 255 {
+                self.nat = Some((packet.x, packet.y));
             } else {
-                let recipient = &mut self.inputs[address];
-                recipient.push_back(packet.0);
-                recipient.push_back(packet.1);
+                let recipient = &mut self.inputs[packet.address];
+                recipient.push_back(packet.x);
+                recipient.push_back(packet.y);
             }
         }
         self.idle[i] = idle;
     }
 
-    fn run_until_idle(&mut self) {
+    /// Runs each computer in turn until it waits for input, delivering the packets it sends.
+    pub fn run_round(&mut self) {
+        for i in 0..50 {
+            self.run_computer(i);
+        }
+    }
+
+    pub fn run_until_idle(&mut self) {
         while !self.is_idle() {
-            for i in 0..50 {
-                self.run_computer(i);
-            }
+            self.run_round();
         }
     }
 
-    fn resume_activity(&mut self) {
+    /// Has the NAT send its last packet to computer 0 if the network is idle.
+    pub fn resume_activity(&mut self) {
         if !self.is_idle() {
             return;
         }
@@ -93,7 +119,7 @@ fn run_until_nat(network: &mut Network) -> i64 {
     loop {
         for i in 0..50 {
             network.run_computer(i);
-            if let Some((x, y)) = network.nat {
+            if let Some((_, y)) = network.nat {
                 return y;
             }
         }
@@ -110,7 +136,7 @@ fn run_until_redelivery(network: &mut Network) -> i64 {
     let mut seen_y = HashSet::new();
     loop {
         network.run_until_idle();
-        if let Some((x, y)) = network.nat {
+        if let Some((_, y)) = network.nat {
             if seen_y.contains(&y) {
                 return y;
             }
@@ -118,4 +144,26 @@ fn run_until_redelivery(network: &mut Network) -> i64 {
         }
         network.resume_activity();
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Computer 0 sends (7, 42) to the NAT at boot, and again whenever it's sent x = 7; every
+    // other computer just waits.
+    const NIC: &str = "3,100,1005,100,11,104,255,104,7,104,42,3,101,1008,101,7,102,1006,102,11,3,103,1106,0,5,99";
+
+    #[test]
+    fn first_packet_to_nat() {
+        let mut network = Network::initialize(&parse(NIC).unwrap());
+        assert_eq!(run_until_nat(&mut network), 42);
+    }
+
+    #[test]
+    fn nat_redelivers() {
+        let mut network = Network::initialize(&parse(NIC).unwrap());
+        assert_eq!(run_until_redelivery(&mut network), 42);
+        assert!(network.is_idle());
+    }
+}
@@ -0,0 +1,203 @@
+use std::cmp::{max, min};
+
+use crate::grid::{Direction, Point};
+use crate::parse::{self, parse_number, ParseError};
+
+#[aoc_generator(day3)]
+fn parse(input: &str) -> Result<Vec<Wire>, ParseError> {
+    let wires = parse::lines(input).map(|l| Ok(Wire::trace(&parse_moves(input, l)?))).collect::<Result<Vec<_>, _>>()?;
+    if wires.len() != 2 {
+        return Err(ParseError::at_end(input, &format!("Expected two wires, found {}", wires.len())));
+    }
+    Ok(wires)
+}
+
+#[aoc(day3, part1)]
+fn part1(wires: &[Wire]) -> i64 {
+    intersections(&wires[0], &wires[1]).iter()
+        .map(|i| i.coordinate.manhattan_distance(&Point::origin()))
+        .min()
+        .expect("Wires never cross!")
+}
+
+#[aoc(day3, part2)]
+fn part2(wires: &[Wire]) -> i64 {
+    intersections(&wires[0], &wires[1]).iter()
+        .map(|i| i.steps)
+        .min()
+        .expect("Wires never cross!")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Move {
+    direction: Direction,
+    distance: i64,
+}
+
+/// Parses one wire's moves from `line`, a slice of `input`.
+fn parse_moves(input: &str, line: &str) -> Result<Vec<Move>, ParseError> {
+    line.split(',')
+        .map(|m| m.trim())
+        .map(|m| {
+            let direction = match m.chars().next() {
+                Some('U') => Direction::Up,
+                Some('R') => Direction::Right,
+                Some('D') => Direction::Down,
+                Some('L') => Direction::Left,
+                _ => return Err(ParseError::at(input, m, "Invalid move")),
+            };
+            let distance = parse_number(input, &m[1..])?;
+            Ok(Move { direction, distance })
+        })
+        .collect()
+}
+
+/// A straight piece of wire, along with how many steps the wire took to reach its start.
+#[derive(Debug, Clone, Copy)]
+struct Segment {
+    start: Point,
+    end: Point,
+    steps: i64,
+}
+
+impl Segment {
+    fn is_horizontal(&self) -> bool {
+        self.start.y == self.end.y
+    }
+
+    fn x_range(&self) -> (i64, i64) {
+        (min(self.start.x, self.end.x), max(self.start.x, self.end.x))
+    }
+
+    fn y_range(&self) -> (i64, i64) {
+        (min(self.start.y, self.end.y), max(self.start.y, self.end.y))
+    }
+
+    fn steps_to(&self, coordinate: &Point) -> i64 {
+        self.steps + self.start.manhattan_distance(coordinate)
+    }
+
+    /// Points shared by both segments. Perpendicular segments share at most one point;
+    /// segments along the same line share every point where they overlap.
+    fn crossings(&self, other: &Segment) -> Vec<Point> {
+        let ((ax0, ax1), (ay0, ay1)) = (self.x_range(), self.y_range());
+        let ((bx0, bx1), (by0, by1)) = (other.x_range(), other.y_range());
+        let (x0, x1) = (max(ax0, bx0), min(ax1, bx1));
+        let (y0, y1) = (max(ay0, by0), min(ay1, by1));
+        if x0 > x1 || y0 > y1 {
+            return vec![];
+        }
+
+        if self.is_horizontal() == other.is_horizontal() {
+            (x0..=x1).flat_map(|x| (y0..=y1).map(move |y| Point::new(x, y))).collect()
+        } else {
+            vec![Point::new(x0, y0)]
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Wire {
+    segments: Vec<Segment>,
+}
+
+impl Wire {
+    fn trace(moves: &[Move]) -> Wire {
+        let mut position = Point::origin();
+        let mut steps = 0;
+        let mut segments = vec![];
+        for m in moves {
+            let end = position.advance(m.direction, m.distance);
+            segments.push(Segment { start: position, end, steps });
+            position = end;
+            steps += m.distance;
+        }
+        Wire { segments }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Intersection {
+    coordinate: Point,
+    /// Combined steps both wires take to first reach the intersection.
+    steps: i64,
+}
+
+fn intersections(a: &Wire, b: &Wire) -> Vec<Intersection> {
+    let origin = Point::origin();
+    let mut intersections: Vec<Intersection> = vec![];
+    for segment_a in &a.segments {
+        for segment_b in &b.segments {
+            for coordinate in segment_a.crossings(segment_b) {
+                if coordinate == origin {
+                    continue;
+                }
+                let steps = segment_a.steps_to(&coordinate) + segment_b.steps_to(&coordinate);
+                match intersections.iter_mut().find(|i| i.coordinate == coordinate) {
+                    Some(existing) => existing.steps = min(existing.steps, steps),
+                    None => intersections.push(Intersection { coordinate, steps }),
+                }
+            }
+        }
+    }
+    intersections
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLES: &[(&str, i64, i64)] = &[
+        ("R8,U5,L5,D3\nU7,R6,D4,L4", 6, 30),
+        ("R75,D30,R83,U83,L12,D49,R71,U7,L72\nU62,R66,U55,R34,D71,R55,D58,R83", 159, 610),
+        ("R98,U47,R26,D63,R33,U87,L62,D20,R33,U53,R51\nU98,R91,D20,R16,D67,R40,U7,R15,U6,R7", 135, 410),
+    ];
+
+    #[test]
+    fn closest_intersection() {
+        for (wires, distance, _) in EXAMPLES {
+            assert_eq!(part1(&parse(wires).unwrap()), *distance, "{}", wires);
+        }
+    }
+
+    #[test]
+    fn fewest_combined_steps() {
+        for (wires, _, steps) in EXAMPLES {
+            assert_eq!(part2(&parse(wires).unwrap()), *steps, "{}", wires);
+        }
+    }
+
+    fn segment(start: (i64, i64), end: (i64, i64)) -> Segment {
+        Segment { start: Point::new(start.0, start.1), end: Point::new(end.0, end.1), steps: 0 }
+    }
+
+    #[test]
+    fn segment_crossings() {
+        let horizontal = segment((0, 2), (5, 2));
+        assert_eq!(horizontal.crossings(&segment((3, 0), (3, 4))), vec![Point::new(3, 2)]);
+        assert_eq!(horizontal.crossings(&segment((3, 3), (3, 4))), vec![]);
+        assert_eq!(horizontal.crossings(&segment((6, 0), (6, 4))), vec![]);
+        assert_eq!(horizontal.crossings(&segment((5, 2), (5, 9))), vec![Point::new(5, 2)]);
+
+        // Overlapping along the same line, in either direction.
+        assert_eq!(horizontal.crossings(&segment((7, 2), (4, 2))), vec![Point::new(4, 2), Point::new(5, 2)]);
+        assert_eq!(horizontal.crossings(&segment((0, 3), (5, 3))), vec![]);
+    }
+
+    #[test]
+    fn traces_steps_along_the_wire() {
+        let input = "R8,U5";
+        let wire = Wire::trace(&parse_moves(input, input).unwrap());
+        assert_eq!(wire.segments[1].steps, 8);
+        assert_eq!(wire.segments[1].steps_to(&Point::new(8, 3)), 11);
+    }
+
+    #[test]
+    fn keeps_fewest_steps_for_revisited_intersections() {
+        // The first wire crosses (2, 0) twice: after 2 steps and after 10.
+        let wires = parse("R4,U2,L2,D4\nD2,R2,U2").unwrap();
+        let found = intersections(&wires[0], &wires[1]);
+        let crossing = found.iter().find(|i| i.coordinate == Point::new(2, 0)).unwrap();
+        assert_eq!(crossing.steps, 2 + 6);
+    }
+}
@@ -1,7 +1,10 @@
+use crate::parse::{parse_number, ParseError};
+
 #[aoc_generator(day4)]
-fn parse(input: &str) -> (u32, u32) {
-    let bounds: Vec<u32> = input.split('-').filter_map(|s| s.parse().ok()).collect();
-    (bounds[0], bounds[1])
+fn parse(input: &str) -> Result<(u32, u32), ParseError> {
+    let range = input.trim();
+    let (min, max) = range.split_once('-').ok_or_else(|| ParseError::at(input, range, "Expected a range like 100000-999999"))?;
+    Ok((parse_number(input, min)?, parse_number(input, max)?))
 }
 
 #[aoc(day4, part1)]
@@ -16,7 +19,7 @@ fn part2((min, max): &(u32, u32)) -> usize {
 
 fn find_passwords_1(min: u32, max: u32) -> Vec<u32> {
     (min..=max)
-        .map(|n| to_digits(n))
+        .map(to_digits)
         .filter(|c| is_six_digits(c))
         .filter(|c| has_two_adjacent_digits(c))
         .filter(|c| is_non_decreasing(c))
@@ -26,7 +29,7 @@ fn find_passwords_1(min: u32, max: u32) -> Vec<u32> {
 
 fn find_passwords_2(min: u32, max: u32) -> Vec<u32> {
     (min..=max)
-        .map(|n| to_digits(n))
+        .map(to_digits)
         .filter(|c| is_six_digits(c))
         .filter(|c| has_two_adjacent_digits(c))
         .filter(|c| is_non_decreasing(c))
@@ -35,19 +38,19 @@ fn find_passwords_2(min: u32, max: u32) -> Vec<u32> {
         .collect()
 }
 
-fn is_six_digits(candidate: &Vec<u32>) -> bool {
+fn is_six_digits(candidate: &[u32]) -> bool {
     candidate.len() == 6
 }
 
-fn has_two_adjacent_digits(candidate: &Vec<u32>) -> bool {
+fn has_two_adjacent_digits(candidate: &[u32]) -> bool {
     candidate.iter().fold((0, false), |acc, digit| (*digit, acc.1 || *digit == acc.0)).1
 }
 
-fn is_non_decreasing(candidate: &Vec<u32>) -> bool {
+fn is_non_decreasing(candidate: &[u32]) -> bool {
     candidate.iter().fold((0, true), |acc, digit| (*digit, acc.1 && *digit >= acc.0)).1
 }
 
-fn has_exact_pair(candidate: &Vec<u32>) -> bool {
+fn has_exact_pair(candidate: &[u32]) -> bool {
     candidate.iter().any(|c| candidate.iter().filter(|x| *x == c).count() == 2)
 }
 
@@ -55,6 +58,33 @@ fn to_digits(number: u32) -> Vec<u32> {
     number.to_string().chars().map(|d| d.to_digit(10).unwrap()).collect()
 }
 
-fn to_number(digits: &Vec<u32>) -> u32 {
+fn to_number(digits: &[u32]) -> u32 {
     digits.iter().map(|d| d.to_string()).collect::<String>().parse().unwrap()
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_password_1(n: u32) -> bool {
+        find_passwords_1(n, n) == vec![n]
+    }
+
+    fn is_password_2(n: u32) -> bool {
+        find_passwords_2(n, n) == vec![n]
+    }
+
+    #[test]
+    fn adjacent_digits_rules() {
+        assert!(is_password_1(111111));
+        assert!(!is_password_1(223450));
+        assert!(!is_password_1(123789));
+    }
+
+    #[test]
+    fn exact_pair_rules() {
+        assert!(is_password_2(112233));
+        assert!(!is_password_2(123444));
+        assert!(is_password_2(111122));
+    }
+}
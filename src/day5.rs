@@ -1,9 +1,10 @@
 use std::collections::VecDeque;
 
 use crate::intcode_computer::{self, Computer};
+use crate::parse::ParseError;
 
 #[aoc_generator(day5)]
-fn parse(input: &str) -> Vec<i64> {
+fn parse(input: &str) -> Result<Vec<i64>, ParseError> {
     intcode_computer::parse_program(input)
 }
 
@@ -27,4 +28,52 @@ fn part2(program: &[i64]) -> i64 {
     input.push_back(5);
     computer.run_with_io(&mut input, &mut output);
     output.pop_back().expect("No diagnostic!")
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(program: &str, input: i64) -> Vec<i64> {
+        let mut computer = Computer::initialize(&parse(program).unwrap());
+        let mut output = vec![];
+        computer.run_with_io(&mut VecDeque::from(vec![input]), &mut output);
+        output
+    }
+
+    #[test]
+    fn parameter_modes() {
+        let mut computer = Computer::initialize(&parse("1002,4,3,4,33").unwrap());
+        computer.run();
+        assert_eq!(computer.memory(), &[1002, 4, 3, 4, 99]);
+        assert_eq!(run("3,0,4,0,99", 42), vec![42]);
+    }
+
+    #[test]
+    fn comparisons() {
+        for (program, expected) in &[
+            ("3,9,8,9,10,9,4,9,99,-1,8", [0, 1, 0]),
+            ("3,9,7,9,10,9,4,9,99,-1,8", [1, 0, 0]),
+            ("3,3,1108,-1,8,3,4,3,99", [0, 1, 0]),
+            ("3,3,1107,-1,8,3,4,3,99", [1, 0, 0]),
+        ] {
+            for (input, output) in [7, 8, 9].iter().zip(expected.iter()) {
+                assert_eq!(run(program, *input), vec![*output], "{} with {}", program, input);
+            }
+        }
+    }
+
+    #[test]
+    fn jumps() {
+        for program in &["3,12,6,12,15,1,13,14,13,4,13,99,-1,0,1,9", "3,3,1105,-1,9,1101,0,0,12,4,12,99,1"] {
+            assert_eq!(run(program, 0), vec![0]);
+            assert_eq!(run(program, 5), vec![1]);
+        }
+
+        let program = "3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,1106,0,36,98,0,0,1002,21,125,20,\
+                       4,20,1105,1,46,104,999,1105,1,46,1101,1000,1,20,4,20,1105,1,46,98,99";
+        assert_eq!(run(program, 7), vec![999]);
+        assert_eq!(run(program, 8), vec![1000]);
+        assert_eq!(run(program, 9), vec![1001]);
+    }
+}
@@ -1,76 +1,239 @@
-use multimap::MultiMap;
 use std::collections::HashMap;
-use std::cmp;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use crate::parse::{self, ParseError};
 
 #[aoc_generator(day6)]
-fn parse(input: &str) -> (MultiMap<String, String>, HashMap<String, String>) {
-    let mut orbiters = MultiMap::new();
-    let mut orbitees = HashMap::new();
-
-    for l in input.lines() {
-        let objects: Vec<&str> = l.split(')').collect();
-        orbiters.insert(objects[0].to_string(), objects[1].to_string());
-        orbitees.insert(objects[1].to_string(), objects[0].to_string());
+fn parse(input: &str) -> Result<OrbitTree, ParseError> {
+    let orbits =
+        parse::lines(input)
+            .map(|l| Orbit::parse(l).ok_or_else(|| ParseError::at(input, l, "Invalid orbit")))
+            .collect::<Result<Vec<_>, _>>()?;
+    OrbitTree::new(input, &orbits)
+}
+
+#[aoc(day6, part1)]
+fn part1(tree: &OrbitTree) -> usize {
+    tree.total_orbits()
+}
+
+#[aoc(day6, part2)]
+fn part2(tree: &OrbitTree) -> usize {
+    tree.transfers("YOU", "SAN").unwrap_or_else(|e| panic!("{}", e))
+}
+
+pub(crate) fn export(input: &str, path: &Path) -> Result<(), Box<dyn Error>> {
+    fs::write(path, parse(input)?.to_dot())?;
+    Ok(())
+}
+
+/// One line of the map, with the bodies still pointing into the input.
+#[derive(Debug)]
+struct Orbit<'a> {
+    line: &'a str,
+    orbitee: &'a str,
+    orbiter: &'a str,
+}
+
+impl<'a> Orbit<'a> {
+    fn parse(line: &'a str) -> Option<Orbit<'a>> {
+        let objects: Vec<&str> = line.split(')').collect();
+        match objects.as_slice() {
+            [orbitee, orbiter] if !orbitee.is_empty() && !orbiter.is_empty() =>
+                Some(Orbit { line, orbitee, orbiter }),
+            _ => None,
+        }
     }
+}
 
-    (orbiters, orbitees)
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum OrbitError {
+    UnknownBody(String),
+    Unorbiting(String),
 }
 
-#[aoc(day6, part1)]
-fn part1((orbiters, _): &(MultiMap<String, String>, HashMap<String, String>)) -> usize {
-    let mut sum = 0;
-    let mut depth = 0;
-    let mut open = vec![String::from("COM")];
-    while open.len() != 0 {
-        depth += 1;
-        open = open.iter().filter_map(|o| orbiters.get_vec(o)).flat_map(|os| os.to_vec()).collect();
-        sum += open.len() * depth;
+impl fmt::Display for OrbitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OrbitError::UnknownBody(body) => write!(f, "Unknown body: {}", body),
+            OrbitError::Unorbiting(body) => write!(f, "{} doesn't orbit anything", body),
+        }
     }
+}
+
+impl std::error::Error for OrbitError {}
 
-    sum
+/// Bodies and what they orbit, rooted at the single body that orbits nothing.
+#[derive(Debug)]
+struct OrbitTree {
+    root: String,
+    orbitees: HashMap<String, String>,
+    orbiters: HashMap<String, Vec<String>>,
+    depths: HashMap<String, usize>,
 }
 
-#[aoc(day6, part2)]
-fn part2((_, orbitees): &(MultiMap<String, String>, HashMap<String, String>)) -> usize {
-    let mut you_parent = &orbitees["YOU"];
-    let mut san_parent = &orbitees["SAN"];
+impl OrbitTree {
+    /// Builds the tree from `orbits`, parsed from the lines of `input`. Maps that aren't a
+    /// single tree are reported at the line that breaks them.
+    fn new(input: &str, orbits: &[Orbit]) -> Result<OrbitTree, ParseError> {
+        let mut orbitees = HashMap::new();
+        let mut orbiters: HashMap<String, Vec<String>> = HashMap::new();
+        for orbit in orbits {
+            if orbitees.insert(orbit.orbiter.to_string(), orbit.orbitee.to_string()).is_some() {
+                let message = format!("{} orbits more than one body", orbit.orbiter);
+                return Err(ParseError::at(input, orbit.line, &message));
+            }
+            orbiters.entry(orbit.orbitee.to_string()).or_default().push(orbit.orbiter.to_string());
+        }
+
+        // Bodies that orbit nothing, with the line each is first mentioned on.
+        let mut roots: Vec<&Orbit> = vec![];
+        for orbit in orbits {
+            if !orbitees.contains_key(orbit.orbitee) && roots.iter().all(|r| r.orbitee != orbit.orbitee) {
+                roots.push(orbit);
+            }
+        }
+        let root = match roots.as_slice() {
+            [] => return Err(ParseError::at_end(input, "Every body orbits something; there is no center of mass")),
+            [root] => root.orbitee.to_string(),
+            [_, second, ..] => {
+                let names: Vec<&str> = roots.iter().map(|r| r.orbitee).collect();
+                let message = format!("Found more than one center of mass: {}", names.join(", "));
+                return Err(ParseError::at(input, second.line, &message));
+            },
+        };
+
+        let mut depths = HashMap::new();
+        let mut open = vec![(root.clone(), 0)];
+        while let Some((body, depth)) = open.pop() {
+            for orbiter in orbiters.get(&body).into_iter().flatten() {
+                open.push((orbiter.clone(), depth + 1));
+            }
+            depths.insert(body, depth);
+        }
+
+        // Every body has at most one orbitee, so anything unreachable from the root is
+        // orbiting in a loop.
+        let mut cycle: Vec<&Orbit> = orbits.iter().filter(|o| !depths.contains_key(o.orbiter)).collect();
+        if let Some(first) = cycle.first() {
+            let line = first.line;
+            cycle.sort_by_key(|o| o.orbiter);
+            let bodies: Vec<&str> = cycle.iter().map(|o| o.orbiter).collect();
+            let message = format!("Bodies orbit in a cycle: {}", bodies.join(", "));
+            return Err(ParseError::at(input, line, &message));
+        }
 
-    let you_depth = calculate_depth(&orbitees, you_parent);
-    let san_depth = calculate_depth(&orbitees, san_parent);
-    let diff = cmp::max(you_depth, san_depth) - cmp::min(you_depth, san_depth);
+        Ok(OrbitTree { root, orbitees, orbiters, depths })
+    }
 
-    if you_depth > san_depth {
-        for _ in 0..diff {
-            you_parent = &orbitees[you_parent];
+    /// Number of direct and indirect orbits around the root.
+    fn depth(&self, body: &str) -> Result<usize, OrbitError> {
+        self.depths.get(body).cloned().ok_or_else(|| OrbitError::UnknownBody(body.to_string()))
+    }
+
+    fn total_orbits(&self) -> usize {
+        self.depths.values().sum()
+    }
+
+    fn orbitee(&self, body: &str) -> Result<&str, OrbitError> {
+        self.depth(body)?;
+        self.orbitees.get(body).map(|b| b.as_str()).ok_or_else(|| OrbitError::Unorbiting(body.to_string()))
+    }
+
+    fn lowest_common_ancestor<'a>(&'a self, a: &'a str, b: &'a str) -> Result<&'a str, OrbitError> {
+        let (mut a, mut b) = (a, b);
+        let (mut depth_a, mut depth_b) = (self.depth(a)?, self.depth(b)?);
+        while depth_a > depth_b {
+            a = &self.orbitees[a];
+            depth_a -= 1;
         }
-    } else {
-        for _ in 0..diff {
-            san_parent = &orbitees[san_parent];
+        while depth_b > depth_a {
+            b = &self.orbitees[b];
+            depth_b -= 1;
         }
+        while a != b {
+            a = &self.orbitees[a];
+            b = &self.orbitees[b];
+        }
+        Ok(a)
     }
 
-    let mut additional = 0;
-    while you_parent != san_parent {
-        additional += 1;
-        you_parent = &orbitees[you_parent];
-        san_parent = &orbitees[san_parent];
+    /// Number of orbits between two bodies in the tree.
+    fn distance(&self, a: &str, b: &str) -> Result<usize, OrbitError> {
+        let ancestor = self.lowest_common_ancestor(a, b)?;
+        Ok(self.depth(a)? + self.depth(b)? - 2 * self.depth(ancestor)?)
     }
 
-    diff + 2*additional
-}
+    /// Orbital transfers needed to move from orbiting what `from` orbits to orbiting what `to`
+    /// orbits.
+    fn transfers(&self, from: &str, to: &str) -> Result<usize, OrbitError> {
+        self.distance(self.orbitee(from)?, self.orbitee(to)?)
+    }
 
-#[derive(Debug)]
-struct Orbit {
-    orbitee: String,
-    orbiter: String,
+    /// The tree in Graphviz DOT format, with an edge from each body to everything orbiting it.
+    fn to_dot(&self) -> String {
+        let mut bodies: Vec<&String> = self.orbiters.keys().collect();
+        bodies.sort();
+
+        let mut dot = String::from("digraph orbits {\n");
+        dot.push_str(&format!("    \"{}\" [shape=doublecircle];\n", self.root));
+        for body in bodies {
+            let mut orbiters = self.orbiters[body].clone();
+            orbiters.sort();
+            for orbiter in orbiters {
+                dot.push_str(&format!("    \"{}\" -> \"{}\";\n", body, orbiter));
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
 }
 
-fn calculate_depth(orbitees: &HashMap<String, String>, object: &str) -> usize {
-    let mut depth = 0;
-    let mut current = object;
-    while current != "COM" {
-        depth += 1;
-        current = &orbitees[current];
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "COM)B\nB)C\nC)D\nD)E\nE)F\nB)G\nG)H\nD)I\nE)J\nJ)K\nK)L";
+
+    #[test]
+    fn counts_orbits() {
+        let tree = parse(EXAMPLE).unwrap();
+        assert_eq!(tree.depth("D"), Ok(3));
+        assert_eq!(tree.depth("L"), Ok(7));
+        assert_eq!(tree.depth("COM"), Ok(0));
+        assert_eq!(part1(&tree), 42);
     }
-    depth
-}
\ No newline at end of file
+
+    #[test]
+    fn counts_transfers() {
+        let tree = parse(&format!("{}\nK)YOU\nI)SAN", EXAMPLE)).unwrap();
+        assert_eq!(tree.lowest_common_ancestor("YOU", "SAN"), Ok("D"));
+        assert_eq!(part2(&tree), 4);
+    }
+
+    #[test]
+    fn draws_dot() {
+        let tree = parse("B)C\nCOM)B\nB)D").unwrap();
+        assert_eq!(
+            tree.to_dot(),
+            "digraph orbits {\n    \"COM\" [shape=doublecircle];\n    \"B\" -> \"C\";\n    \"B\" -> \"D\";\n    \"COM\" -> \"B\";\n}\n");
+    }
+
+    #[test]
+    fn rejects_bad_maps() {
+        assert_eq!(parse("COM)B\nB-C").unwrap_err(), ParseError::new(2, 1, "B-C", "Invalid orbit"));
+        assert_eq!(parse("COM)B\nX)B").unwrap_err(), ParseError::new(2, 1, "X)B", "B orbits more than one body"));
+        assert_eq!(
+            parse("A)B\nB)A").unwrap_err(),
+            ParseError::new(2, 4, "", "Every body orbits something; there is no center of mass"));
+        assert_eq!(
+            parse("COM)B\nX)Y\nY)X").unwrap_err(),
+            ParseError::new(2, 1, "X)Y", "Bodies orbit in a cycle: X, Y"));
+        assert_eq!(
+            parse("COM)B\nX)Y\nB)C").unwrap_err(),
+            ParseError::new(2, 1, "X)Y", "Found more than one center of mass: COM, X"));
+    }
+}
@@ -1,60 +1,100 @@
-use itertools::Itertools;
 use std::collections::VecDeque;
 
-use crate::intcode_computer::{self, Computer};
+use crate::intcode_computer::{self, Computer, ParameterSearch, ParameterSpace};
+use crate::parse::ParseError;
 
 #[aoc_generator(day7)]
-fn parse(input: &str) -> Vec<i64> {
+fn parse(input: &str) -> Result<Vec<i64>, ParseError> {
     intcode_computer::parse_program(input)
 }
 
 #[aoc(day7, part1)]
 fn part1(program: &[i64]) -> i64 {
-    (0..5)
-        .permutations(5)
-        .map(|p| run_series_amplifiers(program, &p))
-        .max()
-        .unwrap()
-}
-
-fn run_series_amplifiers(program: &[i64], phase_settings: &[i64]) -> i64 {
-    let mut io: Vec<VecDeque<i64>> = (0..=phase_settings.len()).map(|_| VecDeque::new()).collect();
-    let mut amplifiers: Vec<Computer> = (0..phase_settings.len()).map(|_| Computer::initialize(program)).collect();
-    for i in 0..phase_settings.len() {
-        io[i].push_back(phase_settings[i]);
-    }
-    io[0].push_back(0);
-    for i in 0..phase_settings.len() {
-        let mut output = 0;
-        amplifiers[i].run_with_io(&mut io[i], &mut |o| output = o);
-        io[i+1].push_back(output);
-    }
-    io[phase_settings.len()].pop_front().expect("No output!")
+    max_signal(program, &[0, 1, 2, 3, 4], Amplifiers::run_series)
 }
 
 #[aoc(day7, part2)]
 fn part2(program: &[i64]) -> i64 {
-    (5..=9)
-        .permutations(5)
-        .map(|p| run_feedback_amplifiers(program, &p))
-        .max()
-        .unwrap()
+    max_signal(program, &[5, 6, 7, 8, 9], Amplifiers::run_feedback)
+}
+
+fn max_signal(program: &[i64], phases: &[i64], run: fn(Amplifiers) -> i64) -> i64 {
+    let search = ParameterSearch::new(program, ParameterSpace::permutations(phases), Amplifiers::new, run);
+    search.maximize().expect("No phase settings!").1
 }
 
-fn run_feedback_amplifiers(program: &[i64], phase_settings: &[i64]) -> i64 {
-    let mut io: Vec<VecDeque<i64>> = (0..phase_settings.len()).map(|_| VecDeque::new()).collect();
-    let mut amplifiers: Vec<Computer> = (0..phase_settings.len()).map(|_| Computer::initialize(program)).collect();
-    for i in 0..phase_settings.len() {
-        io[i].push_back(phase_settings[i]);
+/// A row of amplifiers, each loaded with the program and given its phase setting as its first
+/// input.
+struct Amplifiers {
+    computers: Vec<Computer>,
+    inputs: Vec<VecDeque<i64>>,
+}
+
+impl Amplifiers {
+    fn new(program: &[i64], phase_settings: &[i64]) -> Amplifiers {
+        Amplifiers {
+            computers: phase_settings.iter().map(|_| Computer::initialize(program)).collect(),
+            inputs: phase_settings.iter().map(|phase| VecDeque::from(vec![*phase])).collect(),
+        }
+    }
+
+    /// Passes a signal of 0 through each amplifier in turn.
+    fn run_series(mut self) -> i64 {
+        let mut signal = 0;
+        for (computer, input) in self.computers.iter_mut().zip(self.inputs.iter_mut()) {
+            input.push_back(signal);
+            computer.run_with_io(input, &mut |o| signal = o);
+        }
+        signal
     }
-    io[0].push_back(0);
-    while !amplifiers[phase_settings.len()-1].is_halted() {
-        for i in 0..phase_settings.len() {
-            let mut output = VecDeque::new();
-            amplifiers[i].run_with_io(&mut io[i], &mut output);
-            io[(i+1) % phase_settings.len()].append(&mut output);
+
+    /// Feeds each amplifier's output to the next, and the last one's back to the first, until
+    /// the last one halts.
+    fn run_feedback(mut self) -> i64 {
+        let count = self.computers.len();
+        self.inputs[0].push_back(0);
+        while !self.computers[count - 1].is_halted() {
+            for i in 0..count {
+                let mut output = VecDeque::new();
+                self.computers[i].run_with_io(&mut self.inputs[i], &mut output);
+                self.inputs[(i + 1) % count].append(&mut output);
+            }
         }
+
+        self.inputs[0].pop_front().expect("No output!")
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    io[0].pop_front().expect("No output!")
-}
\ No newline at end of file
+    #[test]
+    fn series_examples() {
+        for (program, phases, signal) in &[
+            ("3,15,3,16,1002,16,10,16,1,16,15,15,4,15,99,0,0", [4, 3, 2, 1, 0], 43210),
+            ("3,23,3,24,1002,24,10,24,1002,23,-1,23,101,5,23,23,1,24,23,23,4,23,99,0,0", [0, 1, 2, 3, 4], 54321),
+            ("3,31,3,32,1002,32,10,32,1001,31,-2,31,1007,31,0,33,1002,33,7,33,1,33,31,31,1,32,31,31,4,31,99,0,0,0",
+             [1, 0, 4, 3, 2], 65210),
+        ] {
+            let program = parse(program).unwrap();
+            assert_eq!(Amplifiers::new(&program, phases).run_series(), *signal);
+            assert_eq!(part1(&program), *signal);
+        }
+    }
+
+    #[test]
+    fn feedback_examples() {
+        for (program, phases, signal) in &[
+            ("3,26,1001,26,-4,26,3,27,1002,27,2,27,1,27,26,27,4,27,1001,28,-1,28,1005,28,6,99,0,0,5",
+             [9, 8, 7, 6, 5], 139629729),
+            ("3,52,1001,52,-5,52,3,53,1,52,56,54,1007,54,5,55,1005,55,26,1001,54,-5,54,1105,1,12,1,53,54,53,1008,54,0,\
+              55,1001,55,1,55,2,53,55,53,4,53,1001,56,-1,56,1005,56,6,99,0,0,0,0,10",
+             [9, 7, 8, 5, 6], 18216),
+        ] {
+            let program = parse(program).unwrap();
+            assert_eq!(Amplifiers::new(&program, phases).run_feedback(), *signal);
+            assert_eq!(part2(&program), *signal);
+        }
+    }
+}
@@ -1,12 +1,52 @@
+use crate::ocr;
+use crate::parse::ParseError;
+use std::error::Error;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
 const WIDTH: usize = 25;
 const HEIGHT: usize = 6;
 
+const BLACK: u32 = 0;
+const WHITE: u32 = 1;
+const TRANSPARENT: u32 = 2;
+
+/// Size of the square each pixel becomes in an exported picture.
+const EXPORT_SCALE: usize = 10;
+
 #[aoc_generator(day8)]
-fn parse(input: &str) -> Vec<Layer> {
-    let pixels: Vec<u32> = input.chars().filter_map(|c| c.to_digit(10)).collect();
-    pixels.chunks(WIDTH * HEIGHT).map(|pixels| Layer { pixels: pixels.to_vec() }).collect()
+fn parse(input: &str) -> Result<SpaceImage, ParseError> {
+    SpaceImage::parse(input, WIDTH, HEIGHT)
+}
+
+#[aoc(day8, part1)]
+fn part1(image: &SpaceImage) -> usize {
+    let fewest_zeros =
+        image.layer_stats()
+            .into_iter()
+            .min_by_key(|s| s.black)
+            .unwrap();
+
+    fewest_zeros.white * fewest_zeros.transparent
+}
+
+#[aoc(day8, part2)]
+fn part2(image: &SpaceImage) -> String {
+    ocr::read(&image.decode().lit())
+}
+
+pub(crate) fn export(input: &str, path: &Path) -> Result<(), Box<dyn Error>> {
+    parse(input)?.decode().write_pgm(path, EXPORT_SCALE)?;
+    Ok(())
+}
+
+pub(crate) fn show(input: &str, out: &mut dyn Write) -> Result<(), Box<dyn Error>> {
+    out.write_all(parse(input)?.decode().to_ansi().as_bytes())?;
+    Ok(())
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 struct Layer {
     pixels: Vec<u32>,
 }
@@ -16,45 +56,189 @@ impl Layer {
         self.pixels.iter().filter(|v| **v == value).count()
     }
 
+    /// Lays this layer over `layer`, letting it show through wherever this one is transparent.
     fn merge(mut self, layer: &Layer) -> Layer {
         self.pixels =
             self.pixels.iter().zip(layer.pixels.iter())
-                .map(|(a, b)| if *a == 2 { *b } else { *a })
+                .map(|(a, b)| if *a == TRANSPARENT { *b } else { *a })
                 .collect();
         self
     }
 }
 
-#[aoc(day8, part1)]
-fn part1(layers: &Vec<Layer>) -> usize {
-    let fewest_zeros =
-        layers.iter()
-            .min_by_key(|l| l.count_values(0))
-            .unwrap();
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct LayerStats {
+    black: usize,
+    white: usize,
+    transparent: usize,
+}
+
+/// An image in the Space Image Format: layers of `width` by `height` pixels, front first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SpaceImage {
+    width: usize,
+    height: usize,
+    layers: Vec<Layer>,
+}
 
-    let ones = fewest_zeros.count_values(1);
-    let twos = fewest_zeros.count_values(2);
+impl SpaceImage {
+    /// Parses a string of digits, ignoring surrounding whitespace. A trailing layer with too
+    /// few pixels is reported where it starts.
+    fn parse(input: &str, width: usize, height: usize) -> Result<SpaceImage, ParseError> {
+        let digits = input.trim();
+        if width == 0 || height == 0 {
+            let message = format!("Image dimensions must be non-zero, got {}x{}", width, height);
+            return Err(ParseError::at(input, digits, &message));
+        }
+        let pixels =
+            digits.char_indices()
+                .map(|(i, c)| c.to_digit(10).ok_or_else(|| ParseError::at(input, &digits[i..i + c.len_utf8()], "Invalid pixel")))
+                .collect::<Result<Vec<_>, _>>()?;
+
+        let layer_size = width * height;
+        if pixels.is_empty() {
+            return Err(ParseError::at_end(input, "Expected at least one layer of pixels"));
+        }
+        let leftover = pixels.len() % layer_size;
+        if leftover != 0 {
+            let message = format!("Incomplete layer: {} of the {} pixels in a {}x{} layer", leftover, layer_size, width, height);
+            return Err(ParseError::at(input, &digits[digits.len() - leftover..], &message));
+        }
+
+        let layers = pixels.chunks(layer_size).map(|pixels| Layer { pixels: pixels.to_vec() }).collect();
+        Ok(SpaceImage { width, height, layers })
+    }
+
+    fn layer_stats(&self) -> Vec<LayerStats> {
+        self.layers.iter()
+            .map(|l| LayerStats {
+                black: l.count_values(BLACK),
+                white: l.count_values(WHITE),
+                transparent: l.count_values(TRANSPARENT),
+            })
+            .collect()
+    }
 
-    ones * twos
+    /// Stacks every layer, front to back. Pixels transparent in all layers stay transparent.
+    fn decode(&self) -> DecodedImage {
+        let initial = Layer { pixels: vec![TRANSPARENT; self.width * self.height] };
+        let layer = self.layers.iter().fold(initial, |acc, layer| acc.merge(layer));
+        DecodedImage { width: self.width, height: self.height, layer }
+    }
 }
 
-#[aoc(day8, part2)]
-fn part2(layers: &Vec<Layer>) -> String {
-    let initial = Layer { pixels: vec![2; WIDTH * HEIGHT] };
-    let full = layers.iter().fold(initial, |acc, layer| acc.merge(layer));
-
-    let mut result = String::new();
-
-    result.push_str("\n");
-    for y in 0..HEIGHT {
-        for x in 0..WIDTH {
-            if full.pixels[WIDTH * y + x] == 0 {
-                result.push_str(" ");
-            } else {
-                result.push_str("▓");
+/// The visible result of stacking every layer of a `SpaceImage`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct DecodedImage {
+    width: usize,
+    height: usize,
+    layer: Layer,
+}
+
+impl DecodedImage {
+    fn rows(&self) -> impl Iterator<Item = &[u32]> {
+        self.layer.pixels.chunks(self.width)
+    }
+
+    /// Which pixels are white.
+    fn lit(&self) -> ocr::Image {
+        self.rows().map(|row| row.iter().map(|p| *p == WHITE).collect()).collect()
+    }
+
+    /// The image as a plain (ASCII) PGM file, with each pixel drawn as a `scale` by `scale`
+    /// square. Transparent pixels come out mid-gray.
+    fn to_pgm(&self, scale: usize) -> String {
+        let mut pgm = format!("P2\n{} {}\n255\n", self.width * scale, self.height * scale);
+        for row in self.rows() {
+            let line: Vec<&str> =
+                row.iter()
+                    .flat_map(|p| std::iter::repeat_n(gray(*p), scale))
+                    .collect();
+            for _ in 0..scale {
+                pgm.push_str(&line.join(" "));
+                pgm.push('\n');
             }
         }
-        result.push_str("\n");
+        pgm
     }
-    result
-}
\ No newline at end of file
+
+    fn write_pgm<P: AsRef<Path>>(&self, path: P, scale: usize) -> io::Result<()> {
+        fs::write(path, self.to_pgm(scale))
+    }
+
+    /// The image drawn with terminal background colors, two columns per pixel so it keeps
+    /// roughly its proportions.
+    fn to_ansi(&self) -> String {
+        let mut ansi = String::new();
+        for row in self.rows() {
+            for pixel in row {
+                let color = match *pixel {
+                    BLACK => "40",
+                    WHITE => "47",
+                    _ => "49",
+                };
+                ansi.push_str(&format!("\x1b[{}m  ", color));
+            }
+            ansi.push_str("\x1b[0m\n");
+        }
+        ansi
+    }
+}
+
+fn gray(pixel: u32) -> &'static str {
+    match pixel {
+        BLACK => "0",
+        WHITE => "255",
+        _ => "128",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempFile;
+
+    #[test]
+    fn parses_layers() {
+        let image = SpaceImage::parse("123456789012", 3, 2).unwrap();
+        assert_eq!(image.layers.len(), 2);
+        assert_eq!(image.layers[1].pixels, vec![7, 8, 9, 0, 1, 2]);
+    }
+
+    #[test]
+    fn rejects_bad_input() {
+        assert_eq!(
+            SpaceImage::parse("0120120", 3, 2),
+            Err(ParseError::new(1, 7, "0", "Incomplete layer: 1 of the 6 pixels in a 3x2 layer")));
+        assert_eq!(
+            SpaceImage::parse("01x012", 3, 2),
+            Err(ParseError::new(1, 3, "x", "Invalid pixel")));
+        assert_eq!(
+            SpaceImage::parse("\n", 3, 2),
+            Err(ParseError::new(2, 1, "", "Expected at least one layer of pixels")));
+        assert_eq!(
+            SpaceImage::parse("012", 0, 2),
+            Err(ParseError::new(1, 1, "012", "Image dimensions must be non-zero, got 0x2")));
+    }
+
+    #[test]
+    fn decodes_example() {
+        let image = SpaceImage::parse("0222112222120000", 2, 2).unwrap();
+        let decoded = image.decode();
+        assert_eq!(decoded.layer.pixels, vec![0, 1, 1, 0]);
+        assert_eq!(decoded.to_pgm(1), "P2\n2 2\n255\n0 255\n255 0\n");
+        assert_eq!(decoded.to_ansi(), "\x1b[40m  \x1b[47m  \x1b[0m\n\x1b[47m  \x1b[40m  \x1b[0m\n");
+    }
+
+    #[test]
+    fn writes_scaled_pgm() {
+        let decoded = SpaceImage::parse("201222", 3, 1).unwrap().decode();
+        let file = TempFile::new("day8.pgm");
+        decoded.write_pgm(file.path(), 2).unwrap();
+        let pgm = fs::read_to_string(file.path()).unwrap();
+
+        let mut lines = pgm.lines();
+        assert_eq!((lines.next(), lines.next(), lines.next()), (Some("P2"), Some("6 2"), Some("255")));
+        assert_eq!(lines.collect::<Vec<_>>(), vec!["128 128 0 0 255 255"; 2]);
+    }
+}
@@ -1,9 +1,10 @@
 use std::collections::VecDeque;
 
 use crate::intcode_computer::{self, Computer};
+use crate::parse::ParseError;
 
 #[aoc_generator(day9)]
-fn parse(input: &str) -> Vec<i64> {
+fn parse(input: &str) -> Result<Vec<i64>, ParseError> {
     intcode_computer::parse_program(input)
 }
 
@@ -27,4 +28,28 @@ fn part2(program: &[i64]) -> i64 {
     input.push_back(2);
     computer.run_with_io(&mut input, &mut output);
     output.pop_back().expect("No diagnostic!")
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(program: &[i64]) -> Vec<i64> {
+        let mut computer = Computer::initialize(program);
+        let mut output = vec![];
+        computer.run_with_io(&mut VecDeque::new(), &mut output);
+        output
+    }
+
+    #[test]
+    fn quine() {
+        let program = parse("109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99").unwrap();
+        assert_eq!(run(&program), program);
+    }
+
+    #[test]
+    fn large_numbers() {
+        assert_eq!(run(&parse("1102,34915192,34915192,7,4,7,99,0").unwrap()), vec![1219070632396864]);
+        assert_eq!(run(&parse("104,1125899906842624,99").unwrap()), vec![1125899906842624]);
+    }
+}
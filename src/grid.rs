@@ -0,0 +1,411 @@
+use num::PrimInt;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// A point on a 2D grid. `y` grows downward, matching how puzzle maps are drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point<T = i64> {
+    pub x: T,
+    pub y: T,
+}
+
+impl<T: PrimInt> Point<T> {
+    pub fn new(x: T, y: T) -> Point<T> {
+        Point { x, y }
+    }
+
+    pub fn origin() -> Point<T> {
+        Point::new(T::zero(), T::zero())
+    }
+
+    /// The point `distance` steps away in `direction`, or `None` if that leaves the range of `T`.
+    pub fn checked_advance(&self, direction: Direction, distance: T) -> Option<Point<T>> {
+        match direction {
+            Direction::Up => self.y.checked_sub(&distance).map(|y| Point::new(self.x, y)),
+            Direction::Right => self.x.checked_add(&distance).map(|x| Point::new(x, self.y)),
+            Direction::Down => self.y.checked_add(&distance).map(|y| Point::new(self.x, y)),
+            Direction::Left => self.x.checked_sub(&distance).map(|x| Point::new(x, self.y)),
+        }
+    }
+
+    pub fn advance(&self, direction: Direction, distance: T) -> Point<T> {
+        self.checked_advance(direction, distance).expect("Point out of range!")
+    }
+
+    pub fn step(&self, direction: Direction) -> Point<T> {
+        self.advance(direction, T::one())
+    }
+
+    /// Orthogonal neighbors, skipping any outside the range of `T`.
+    pub fn neighbors(&self) -> Vec<Point<T>> {
+        Direction::ALL.iter().filter_map(|d| self.checked_advance(*d, T::one())).collect()
+    }
+
+    /// Orthogonal and diagonal neighbors, skipping any outside the range of `T`.
+    pub fn neighbors8(&self) -> Vec<Point<T>> {
+        Direction::ALL.iter()
+            .filter_map(|d| self.checked_advance(*d, T::one()))
+            .chain(
+                Direction::ALL.iter()
+                    .filter_map(|d| self.checked_advance(*d, T::one()).and_then(|p| p.checked_advance(d.turn_right(), T::one())))
+            )
+            .collect()
+    }
+
+    pub fn manhattan_distance(&self, other: &Point<T>) -> T {
+        abs_diff(self.x, other.x) + abs_diff(self.y, other.y)
+    }
+}
+
+fn abs_diff<T: PrimInt>(a: T, b: T) -> T {
+    if a > b { a - b } else { b - a }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up, Right, Down, Left
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Turn {
+    Left, Right
+}
+
+impl Direction {
+    /// Clockwise, starting from up.
+    pub const ALL: [Direction; 4] = [Direction::Up, Direction::Right, Direction::Down, Direction::Left];
+
+    pub fn turn(&self, turn: Turn) -> Direction {
+        match turn {
+            Turn::Left => self.turn_left(),
+            Turn::Right => self.turn_right(),
+        }
+    }
+
+    pub fn turn_right(&self) -> Direction {
+        match self {
+            Direction::Up => Direction::Right,
+            Direction::Right => Direction::Down,
+            Direction::Down => Direction::Left,
+            Direction::Left => Direction::Up,
+        }
+    }
+
+    pub fn turn_left(&self) -> Direction {
+        match self {
+            Direction::Up => Direction::Left,
+            Direction::Right => Direction::Up,
+            Direction::Down => Direction::Right,
+            Direction::Left => Direction::Down,
+        }
+    }
+
+    pub fn reverse(&self) -> Direction {
+        self.turn_right().turn_right()
+    }
+}
+
+/// The smallest rectangle containing a set of points, with inclusive bounds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoundingBox<T = i64> {
+    pub min: Point<T>,
+    pub max: Point<T>,
+}
+
+impl<T: PrimInt> BoundingBox<T> {
+    pub fn of<'a, I>(points: I) -> Option<BoundingBox<T>>
+    where
+        I: IntoIterator<Item = &'a Point<T>>,
+        T: 'a,
+    {
+        points.into_iter().fold(None, |bounds, p| match bounds {
+            None => Some(BoundingBox { min: *p, max: *p }),
+            Some(b) => Some(BoundingBox {
+                min: Point::new(b.min.x.min(p.x), b.min.y.min(p.y)),
+                max: Point::new(b.max.x.max(p.x), b.max.y.max(p.y)),
+            }),
+        })
+    }
+
+    pub fn width(&self) -> T {
+        self.max.x - self.min.x + T::one()
+    }
+
+    pub fn height(&self) -> T {
+        self.max.y - self.min.y + T::one()
+    }
+
+    pub fn contains(&self, point: &Point<T>) -> bool {
+        point.x >= self.min.x && point.x <= self.max.x && point.y >= self.min.y && point.y <= self.max.y
+    }
+
+    /// Whether `point` lies on the edge of the box.
+    pub fn is_edge(&self, point: &Point<T>) -> bool {
+        self.contains(point)
+            && (point.x == self.min.x || point.x == self.max.x || point.y == self.min.y || point.y == self.max.y)
+    }
+
+    /// Every point in the box, row by row.
+    pub fn points(&self) -> impl Iterator<Item = Point<T>> {
+        let (min, max) = (self.min, self.max);
+        num::range_inclusive(min.y, max.y)
+            .flat_map(move |y| num::range_inclusive(min.x, max.x).map(move |x| Point::new(x, y)))
+    }
+}
+
+/// A rectangular grid storing a value for every cell.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DenseGrid<C> {
+    width: usize,
+    height: usize,
+    cells: Vec<C>,
+}
+
+impl<C> DenseGrid<C> {
+    pub fn new(width: usize, height: usize, fill: C) -> DenseGrid<C>
+    where
+        C: Clone,
+    {
+        DenseGrid { width, height, cells: vec![fill; width * height] }
+    }
+
+    /// Parses a character map, one row per line. Short lines are padded as if they ended in
+    /// spaces.
+    pub fn parse<F: Fn(char) -> C>(input: &str, cell: F) -> DenseGrid<C> {
+        let width = input.lines().map(|l| l.chars().count()).max().unwrap_or(0);
+        let height = input.lines().count();
+        let cells = input.lines()
+            .flat_map(|l| l.chars().chain(std::iter::repeat(' ')).take(width))
+            .map(cell)
+            .collect();
+        DenseGrid { width, height, cells }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, point: &Point<usize>) -> Option<&C> {
+        if point.x < self.width && point.y < self.height {
+            self.cells.get(point.y * self.width + point.x)
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, point: &Point<usize>) -> Option<&mut C> {
+        if point.x < self.width && point.y < self.height {
+            self.cells.get_mut(point.y * self.width + point.x)
+        } else {
+            None
+        }
+    }
+
+    pub fn set(&mut self, point: &Point<usize>, value: C) {
+        *self.get_mut(point).expect("Point outside grid!") = value;
+    }
+
+    /// Every cell along with its position, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = (Point<usize>, &C)> {
+        let width = self.width;
+        self.cells.iter().enumerate().map(move |(i, c)| (Point::new(i % width, i / width), c))
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[C]> {
+        self.cells.chunks(self.width.max(1))
+    }
+
+    pub fn render<F: Fn(&C) -> char>(&self, cell: F) -> String {
+        let mut result = String::new();
+        for row in self.rows() {
+            result.extend(row.iter().map(&cell));
+            result.push('\n');
+        }
+        result
+    }
+}
+
+/// A grid storing values only for the cells that have been set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SparseGrid<C, T = i64>
+where
+    T: Hash + Eq,
+{
+    cells: HashMap<Point<T>, C>,
+}
+
+impl<C, T: PrimInt + Hash> SparseGrid<C, T> {
+    pub fn new() -> SparseGrid<C, T> {
+        SparseGrid { cells: HashMap::new() }
+    }
+
+    /// Parses a character map, keeping the cells for which `cell` returns a value.
+    pub fn parse<F: Fn(char) -> Option<C>>(input: &str, cell: F) -> SparseGrid<C, T> {
+        let cells = input.lines().enumerate()
+            .flat_map(|(y, l)| l.chars().enumerate().map(move |(x, c)| (x, y, c)))
+            .filter_map(|(x, y, c)| {
+                let point = Point::new(T::from(x)?, T::from(y)?);
+                cell(c).map(|value| (point, value))
+            })
+            .collect();
+        SparseGrid { cells }
+    }
+
+    pub fn get(&self, point: &Point<T>) -> Option<&C> {
+        self.cells.get(point)
+    }
+
+    pub fn insert(&mut self, point: Point<T>, value: C) -> Option<C> {
+        self.cells.insert(point, value)
+    }
+
+    pub fn contains(&self, point: &Point<T>) -> bool {
+        self.cells.contains_key(point)
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Point<T>, &C)> {
+        self.cells.iter()
+    }
+
+    pub fn points(&self) -> impl Iterator<Item = &Point<T>> {
+        self.cells.keys()
+    }
+
+    pub fn bounds(&self) -> Option<BoundingBox<T>> {
+        BoundingBox::of(self.cells.keys())
+    }
+
+    /// Draws the bounding box of the set cells, passing `None` for cells that were never set.
+    pub fn render<F: Fn(Option<&C>) -> char>(&self, cell: F) -> String {
+        let mut result = String::new();
+        if let Some(bounds) = self.bounds() {
+            let mut row = bounds.min.y;
+            for point in bounds.points() {
+                if point.y != row {
+                    result.push('\n');
+                    row = point.y;
+                }
+                result.push(cell(self.get(&point)));
+            }
+            result.push('\n');
+        }
+        result
+    }
+}
+
+impl<C, T: PrimInt + Hash> Default for SparseGrid<C, T> {
+    fn default() -> SparseGrid<C, T> {
+        SparseGrid::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn turns_compose() {
+        for direction in Direction::ALL.iter() {
+            assert_eq!(direction.turn_left().turn_right(), *direction);
+            assert_eq!(direction.turn(Turn::Right).turn(Turn::Right), direction.reverse());
+            assert_eq!(direction.reverse().reverse(), *direction);
+            let around = (0..4).fold(*direction, |d, _| d.turn(Turn::Left));
+            assert_eq!(around, *direction);
+        }
+        assert_eq!(Direction::Up.turn_right(), Direction::Right);
+        assert_eq!(Direction::Up.turn_left(), Direction::Left);
+    }
+
+    #[test]
+    fn moves_and_measures() {
+        let p = Point::new(2, -1);
+        assert_eq!(p.step(Direction::Up), Point::new(2, -2));
+        assert_eq!(p.advance(Direction::Left, 3), Point::new(-1, -1));
+        assert_eq!(p.manhattan_distance(&Point::origin()), 3);
+        assert_eq!(Point::new(0usize, 5).manhattan_distance(&Point::new(3, 1)), 7);
+    }
+
+    #[test]
+    fn neighbors_stay_in_range() {
+        let corner: Point<usize> = Point::origin();
+        assert_eq!(corner.checked_advance(Direction::Up, 1), None);
+        assert_eq!(corner.neighbors(), vec![Point::new(1, 0), Point::new(0, 1)]);
+        assert_eq!(corner.neighbors8(), vec![Point::new(1, 0), Point::new(0, 1), Point::new(1, 1)]);
+
+        let mut around = Point::new(0, 0).neighbors8();
+        around.sort();
+        let expected: Vec<Point> = BoundingBox { min: Point::new(-1, -1), max: Point::new(1, 1) }
+            .points()
+            .filter(|p| *p != Point::origin())
+            .collect::<std::collections::BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        assert_eq!(around, expected);
+    }
+
+    #[test]
+    fn bounding_boxes() {
+        let points = [Point::new(1, 3), Point::new(-1, 2), Point::new(0, 4)];
+        let bounds = BoundingBox::of(&points).unwrap();
+        assert_eq!(bounds, BoundingBox { min: Point::new(-1, 2), max: Point::new(1, 4) });
+        assert_eq!((bounds.width(), bounds.height()), (3, 3));
+        assert!(bounds.contains(&Point::new(0, 3)) && !bounds.contains(&Point::new(2, 3)));
+        assert!(bounds.is_edge(&Point::new(1, 3)) && !bounds.is_edge(&Point::new(0, 3)));
+        assert!(!bounds.is_edge(&Point::new(2, 2)));
+        assert_eq!(BoundingBox::<i64>::of(&[]), None);
+
+        let small = BoundingBox { min: Point::new(5, -1), max: Point::new(6, 0) };
+        assert_eq!(
+            small.points().collect::<Vec<_>>(),
+            vec![Point::new(5, -1), Point::new(6, -1), Point::new(5, 0), Point::new(6, 0)]);
+    }
+
+    #[test]
+    fn dense_grid_round_trip() {
+        let map = "#..\n.#.\n..#\n";
+        let grid = DenseGrid::parse(map, |c| c == '#');
+        assert_eq!((grid.width(), grid.height()), (3, 3));
+        assert_eq!(grid.get(&Point::new(1, 1)), Some(&true));
+        assert_eq!(grid.get(&Point::new(3, 0)), None);
+        assert_eq!(grid.render(|wall| if *wall { '#' } else { '.' }), map);
+
+        let cells: Vec<(Point<usize>, char)> = DenseGrid::parse("ab\ncd", |c| c).iter().map(|(p, c)| (p, *c)).collect();
+        assert_eq!(cells, vec![(Point::new(0, 0), 'a'), (Point::new(1, 0), 'b'), (Point::new(0, 1), 'c'), (Point::new(1, 1), 'd')]);
+    }
+
+    #[test]
+    fn dense_grid_pads_short_lines() {
+        let mut grid = DenseGrid::parse("ab\nc", |c| c);
+        assert_eq!(grid.render(|c| *c), "ab\nc \n");
+        grid.set(&Point::new(1, 1), 'd');
+        assert_eq!(grid.rows().collect::<Vec<_>>(), vec![&['a', 'b'][..], &['c', 'd'][..]]);
+    }
+
+    #[test]
+    fn sparse_grid_round_trip() {
+        let map = "..#\n#..\n.#.\n";
+        let grid: SparseGrid<()> = SparseGrid::parse(map, |c| if c == '#' { Some(()) } else { None });
+        assert_eq!(grid.len(), 3);
+        assert!(grid.contains(&Point::new(2, 0)));
+        assert_eq!(grid.render(|c| if c.is_some() { '#' } else { '.' }), map);
+
+        // Rendering only covers the cells that were set.
+        let mut grid = SparseGrid::new();
+        grid.insert(Point::new(-2, 5), 'x');
+        grid.insert(Point::new(-1, 6), 'y');
+        assert_eq!(grid.render(|c| *c.unwrap_or(&' ')), "x \n y\n");
+        assert_eq!(SparseGrid::<char>::new().render(|_| '?'), "");
+    }
+}
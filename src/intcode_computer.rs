@@ -1,7 +1,22 @@
 use std::collections::VecDeque;
 
-pub fn parse_program(input: &str) -> Vec<i64> {
-    input.split(',').filter_map(|v| v.parse().ok()).collect()
+use crate::parse::{parse_number, ParseError};
+
+mod adapters;
+mod inspect;
+mod parameter_search;
+mod patch;
+mod session;
+
+pub use self::adapters::{Bounded, FanOut, FnInput, FrameDecoder, IterInput, Tee, TextInput, TextInputError, TextOutput};
+pub use self::inspect::{disassemble, dump, ChangedRange, DisassembledInstruction, DumpOptions, MemoryDiff};
+pub use self::parameter_search::{ParameterSearch, ParameterSpace};
+pub use self::patch::{AppliedPatch, Patch, PatchError, PatchSet};
+pub use self::session::{Activity, Divergence, Event, Session, SessionError};
+
+/// Parses comma separated values, failing on the first one that isn't a number.
+pub fn parse_program(input: &str) -> Result<Vec<i64>, ParseError> {
+    input.trim_end().split(',').map(|v| parse_number(input, v.trim())).collect()
 }
 
 pub trait Input {
@@ -25,6 +40,12 @@ impl Input for VecDeque<i64> {
 
 pub trait Output {
     fn write_output(&mut self, output: i64);
+
+    /// Whether the output can accept another value. The computer pauses before an output
+    /// instruction when this is false, the same way it pauses when input runs out.
+    fn is_ready(&self) -> bool {
+        true
+    }
 }
 
 impl<T> Output for T
@@ -42,6 +63,12 @@ impl Output for VecDeque<i64> {
     }
 }
 
+impl Output for Vec<i64> {
+    fn write_output(&mut self, output: i64) {
+        self.push(output);
+    }
+}
+
 
 #[derive(Debug)]
 enum Opcode {
@@ -59,18 +86,22 @@ enum Opcode {
 
 impl Opcode {
     fn parse(value: i64) -> Opcode {
+        Opcode::try_parse(value).unwrap_or_else(|| panic!("Invalid opcode! {}", value))
+    }
+
+    fn try_parse(value: i64) -> Option<Opcode> {
         match value {
-            1 => Opcode::Add,
-            2 => Opcode::Mul,
-            3 => Opcode::Input,
-            4 => Opcode::Output,
-            5 => Opcode::JumpIfTrue,
-            6 => Opcode::JumpIfFalse,
-            7 => Opcode::LessThan,
-            8 => Opcode::Equals,
-            9 => Opcode::RelativeBaseOffset,
-            99 => Opcode::Halt,
-            _ => panic!("Invalid opcode! {}", value)
+            1 => Some(Opcode::Add),
+            2 => Some(Opcode::Mul),
+            3 => Some(Opcode::Input),
+            4 => Some(Opcode::Output),
+            5 => Some(Opcode::JumpIfTrue),
+            6 => Some(Opcode::JumpIfFalse),
+            7 => Some(Opcode::LessThan),
+            8 => Some(Opcode::Equals),
+            9 => Some(Opcode::RelativeBaseOffset),
+            99 => Some(Opcode::Halt),
+            _ => None,
         }
     }
 
@@ -97,11 +128,15 @@ enum ParameterMode {
 
 impl ParameterMode {
     fn of(input: i64) -> ParameterMode {
+        ParameterMode::try_of(input).expect("Invalid parameter mode!")
+    }
+
+    fn try_of(input: i64) -> Option<ParameterMode> {
         match input {
-            0 => ParameterMode::Position,
-            1 => ParameterMode::Immediate,
-            2 => ParameterMode::Relative,
-            _ => panic!("Invalid parameter mode!"),
+            0 => Some(ParameterMode::Position),
+            1 => Some(ParameterMode::Immediate),
+            2 => Some(ParameterMode::Relative),
+            _ => None,
         }
     }
 }
@@ -129,6 +164,7 @@ pub struct Computer {
     instruction_pointer: usize,
     halted: bool,
     relative_base: i64,
+    patches: Vec<AppliedPatch>,
 }
 
 impl Computer {
@@ -138,13 +174,45 @@ impl Computer {
             instruction_pointer: 0,
             halted: false,
             relative_base: 0,
+            patches: vec![],
+        }
+    }
+
+    /// Initializes with `patches` applied in order. Fails if any patch writes past the end of
+    /// the program, or sets an address an earlier patch already set to something else.
+    pub fn initialize_patched(program: &[i64], patches: &[&Patch]) -> Result<Computer, PatchError> {
+        let mut computer = Computer::initialize(program);
+        for patch in patches {
+            patch.check_conflicts(&computer.patches)?;
+            let applied = patch.apply(&mut computer.memory)?;
+            computer.patches.extend(applied);
         }
+        Ok(computer)
+    }
+
+    /// Every assignment made by patches during initialization, in the order applied.
+    pub fn applied_patches(&self) -> &[AppliedPatch] {
+        &self.patches
     }
 
     pub fn is_halted(&self) -> bool {
         self.halted
     }
 
+    pub fn memory(&self) -> &[i64] {
+        &self.memory
+    }
+
+    /// Copy of the current memory, for diffing against a later point in the run.
+    pub fn snapshot(&self) -> Vec<i64> {
+        self.memory.clone()
+    }
+
+    /// Changes between this computer's memory and `other`'s.
+    pub fn diff(&self, other: &Computer) -> MemoryDiff {
+        MemoryDiff::between(&self.memory, &other.memory)
+    }
+
     pub fn run(&mut self) {
         self.run_with_io(&mut || 0, &mut |_| {});
     }
@@ -163,12 +231,15 @@ impl Computer {
             panic!("Must access address >= 0!");
         }
         if address as usize >= self.memory.len() {
-            self.memory.resize(self.memory.len() * 2, 0);
+            let size = (self.memory.len() * 2).max(address as usize + 1);
+            self.memory.resize(size, 0);
         }
         self.memory.get_mut(address as usize).unwrap()
     }
 
-    fn step<I: Input, O: Output>(&mut self, input: &mut I, output: &mut O) -> bool {
+    /// Executes a single instruction. Returns false, leaving the computer where it was, if it
+    /// has halted or is waiting on input or output.
+    pub fn step<I: Input, O: Output>(&mut self, input: &mut I, output: &mut O) -> bool {
         if self.halted {
             return false;
         }
@@ -186,7 +257,7 @@ impl Computer {
         for i in 1..=opcode.num_parameters() {
             let mode = ParameterMode::of(modes % 10);
             let parameter = Parameter {
-                mode: mode,
+                mode,
                 value: *self.access((self.instruction_pointer + i) as i64),
             };
             parameters.push(parameter);
@@ -214,15 +285,22 @@ impl Computer {
                 } else {
                     return false
                 },
-            Opcode::Output => output.write_output(self.read(&parameters[0])),
+            Opcode::Output =>
+                if output.is_ready() {
+                    output.write_output(self.read(&parameters[0]));
+                } else {
+                    return false
+                },
             Opcode::JumpIfTrue => {
                 if self.read(&parameters[0]) != 0 {
-                    self.instruction_pointer = self.read(&parameters[1]) as usize;
+                    let address = self.read(&parameters[1]);
+                    self.jump_to(address);
                 }
             },
             Opcode::JumpIfFalse => {
                 if self.read(&parameters[0]) == 0 {
-                    self.instruction_pointer = self.read(&parameters[1]) as usize;
+                    let address = self.read(&parameters[1]);
+                    self.jump_to(address);
                 }
             },
             Opcode::LessThan => {
@@ -262,3 +340,20 @@ impl Computer {
         self.instruction_pointer = address as usize;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grows_memory_past_twice_its_length() {
+        // Adds 2 and 3 into address 100, then outputs address 1000.
+        let mut computer = Computer::initialize(&[1101, 2, 3, 100, 4, 1000, 99]);
+        let mut output = vec![];
+        computer.run_with_io(&mut || 0, &mut output);
+        assert!(computer.is_halted());
+        assert_eq!(output, vec![0]);
+        assert_eq!(*computer.access(100), 5);
+        assert_eq!(*computer.access(5000), 0);
+    }
+}
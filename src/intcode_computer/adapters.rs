@@ -0,0 +1,414 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::io::{self, BufRead, Write};
+
+use super::{Input, Output};
+
+/// Feeds values from any iterator, pausing the computer once it is exhausted.
+pub struct IterInput<I>(pub I);
+
+impl<I> IterInput<I>
+where
+    I: Iterator<Item = i64>
+{
+    pub fn new<T: IntoIterator<IntoIter = I>>(values: T) -> IterInput<I> {
+        IterInput(values.into_iter())
+    }
+}
+
+impl<I> Input for IterInput<I>
+where
+    I: Iterator<Item = i64>
+{
+    fn read_input(&mut self) -> Option<i64> {
+        self.0.next()
+    }
+}
+
+/// Input callback that can apply backpressure by returning `None`.
+pub struct FnInput<F>(pub F);
+
+impl<F> Input for FnInput<F>
+where
+    F: FnMut() -> Option<i64>
+{
+    fn read_input(&mut self) -> Option<i64> {
+        (self.0)()
+    }
+}
+
+/// Reads integers separated by whitespace or commas from a text stream. A read failure or a
+/// token that isn't an integer pauses the computer as if input ran out, and is kept in
+/// `error`.
+pub struct TextInput<R> {
+    reader: R,
+    pending: VecDeque<i64>,
+    error: Option<TextInputError>,
+}
+
+impl<R: BufRead> TextInput<R> {
+    pub fn new(reader: R) -> TextInput<R> {
+        TextInput { reader, pending: VecDeque::new(), error: None }
+    }
+
+    /// Why input stopped early, if it did.
+    pub fn error(&self) -> Option<&TextInputError> {
+        self.error.as_ref()
+    }
+}
+
+impl<R: BufRead> Input for TextInput<R> {
+    fn read_input(&mut self) -> Option<i64> {
+        while self.pending.is_empty() && self.error.is_none() {
+            let mut line = String::new();
+            match self.reader.read_line(&mut line) {
+                Ok(0) => return None,
+                Ok(_) => {},
+                Err(e) => self.error = Some(TextInputError::Io(e.to_string())),
+            }
+            for token in line.split(|c: char| c == ',' || c.is_whitespace()).filter(|t| !t.is_empty()) {
+                match token.parse() {
+                    Ok(value) => self.pending.push_back(value),
+                    Err(_) => {
+                        self.error = Some(TextInputError::InvalidValue(token.to_string()));
+                        break;
+                    },
+                }
+            }
+        }
+        self.pending.pop_front()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TextInputError {
+    Io(String),
+    InvalidValue(String),
+}
+
+impl fmt::Display for TextInputError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TextInputError::Io(e) => write!(f, "Can't read input: {}", e),
+            TextInputError::InvalidValue(token) => write!(f, "Invalid input value: {}", token),
+        }
+    }
+}
+
+impl std::error::Error for TextInputError {}
+
+/// Writes each output on its own line of a text stream. A failed write, such as to a closed
+/// pipe, is kept in `error` and pauses the computer before its next output.
+pub struct TextOutput<W> {
+    writer: W,
+    error: Option<io::Error>,
+}
+
+impl<W: Write> TextOutput<W> {
+    pub fn new(writer: W) -> TextOutput<W> {
+        TextOutput { writer, error: None }
+    }
+
+    /// Why output stopped, if it did.
+    pub fn error(&self) -> Option<&io::Error> {
+        self.error.as_ref()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> Output for TextOutput<W> {
+    fn write_output(&mut self, output: i64) {
+        if self.error.is_none() {
+            if let Err(e) = writeln!(self.writer, "{}", output) {
+                self.error = Some(e);
+            }
+        }
+    }
+
+    fn is_ready(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Sends every output to both wrapped outputs.
+pub struct Tee<A, B>(pub A, pub B);
+
+impl<A: Output, B: Output> Output for Tee<A, B> {
+    fn write_output(&mut self, output: i64) {
+        self.0.write_output(output);
+        self.1.write_output(output);
+    }
+
+    fn is_ready(&self) -> bool {
+        self.0.is_ready() && self.1.is_ready()
+    }
+}
+
+/// Sends every output to any number of outputs.
+pub struct FanOut<'a> {
+    outputs: Vec<&'a mut dyn Output>,
+}
+
+impl<'a> FanOut<'a> {
+    pub fn new(outputs: Vec<&'a mut dyn Output>) -> FanOut<'a> {
+        FanOut { outputs }
+    }
+}
+
+impl<'a> Output for FanOut<'a> {
+    fn write_output(&mut self, output: i64) {
+        self.outputs.iter_mut().for_each(|o| o.write_output(output));
+    }
+
+    fn is_ready(&self) -> bool {
+        self.outputs.iter().all(|o| o.is_ready())
+    }
+}
+
+/// Queue that holds at most `capacity` values. Used as an output it pauses the producer
+/// once full; used as an input it drains in order.
+pub struct Bounded {
+    queue: VecDeque<i64>,
+    capacity: usize,
+}
+
+impl Bounded {
+    pub fn new(capacity: usize) -> Bounded {
+        Bounded { queue: VecDeque::with_capacity(capacity), capacity }
+    }
+
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+}
+
+impl Input for Bounded {
+    fn read_input(&mut self) -> Option<i64> {
+        self.queue.pop_front()
+    }
+}
+
+impl Output for Bounded {
+    fn write_output(&mut self, output: i64) {
+        self.queue.push_back(output);
+    }
+
+    fn is_ready(&self) -> bool {
+        self.queue.len() < self.capacity
+    }
+}
+
+/// Decodes outputs of a fixed-width protocol, queueing each value as its frame completes.
+pub struct FrameDecoder<T, D> {
+    width: usize,
+    buffer: Vec<i64>,
+    decode: D,
+    frames: VecDeque<T>,
+}
+
+impl<T, D> FrameDecoder<T, D>
+where
+    D: FnMut(&[i64]) -> T
+{
+    pub fn new(width: usize, decode: D) -> FrameDecoder<T, D> {
+        if width == 0 {
+            panic!("Frame width must be positive!");
+        }
+        FrameDecoder {
+            width,
+            buffer: Vec::with_capacity(width),
+            decode,
+            frames: VecDeque::new(),
+        }
+    }
+
+    pub fn next_frame(&mut self) -> Option<T> {
+        self.frames.pop_front()
+    }
+
+    pub fn drain(&mut self) -> impl Iterator<Item = T> + '_ {
+        self.frames.drain(..)
+    }
+
+    /// Values received since the last complete frame.
+    pub fn partial(&self) -> &[i64] {
+        &self.buffer
+    }
+
+    /// Whether there are neither decoded frames nor partial values waiting.
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty() && self.buffer.is_empty()
+    }
+}
+
+impl<T, D> Output for FrameDecoder<T, D>
+where
+    D: FnMut(&[i64]) -> T
+{
+    fn write_output(&mut self, output: i64) {
+        self.buffer.push(output);
+        if self.buffer.len() == self.width {
+            let frame = (self.decode)(&self.buffer);
+            self.frames.push_back(frame);
+            self.buffer.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode_computer::Computer;
+
+    // Reads a value and writes it back, forever.
+    const ECHO: &[i64] = &[3, 7, 4, 7, 1105, 1, 0, 0];
+    const COUNT_TO_THREE: &[i64] = &[104, 1, 104, 2, 104, 3, 99];
+
+    #[test]
+    fn iter_input_pauses_when_exhausted() {
+        let mut computer = Computer::initialize(ECHO);
+        let mut output = vec![];
+        computer.run_with_io(&mut IterInput::new(vec![4, 5, 6]), &mut output);
+        assert_eq!(output, vec![4, 5, 6]);
+        assert!(!computer.is_halted());
+    }
+
+    #[test]
+    fn fn_input_applies_backpressure() {
+        let mut computer = Computer::initialize(ECHO);
+        let mut available = 2;
+        let mut output = vec![];
+        computer.run_with_io(&mut FnInput(|| if available > 0 { available -= 1; Some(7) } else { None }), &mut output);
+        assert_eq!(output, vec![7, 7]);
+
+        computer.run_with_io(&mut FnInput(|| Some(8)), &mut Bounded::new(1));
+        assert!(!computer.is_halted());
+    }
+
+    #[test]
+    fn bounded_output_pauses_until_drained() {
+        let mut computer = Computer::initialize(COUNT_TO_THREE);
+        let mut queue = Bounded::new(2);
+        computer.run_with_io(&mut || 0, &mut queue);
+        assert_eq!(queue.len(), 2);
+        assert!(!computer.is_halted());
+
+        // Paused runs leave the computer where it was.
+        computer.run_with_io(&mut || 0, &mut queue);
+        assert_eq!(queue.len(), 2);
+
+        assert_eq!(queue.read_input(), Some(1));
+        computer.run_with_io(&mut || 0, &mut queue);
+        assert!(computer.is_halted());
+        assert_eq!((queue.read_input(), queue.read_input(), queue.read_input()), (Some(2), Some(3), None));
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn tee_duplicates_and_waits_for_both() {
+        let mut computer = Computer::initialize(COUNT_TO_THREE);
+        let mut tee = Tee(vec![], Bounded::new(1));
+        computer.run_with_io(&mut || 0, &mut tee);
+        assert_eq!(tee.0, vec![1]);
+        assert!(!computer.is_halted());
+
+        tee.1.read_input();
+        computer.run_with_io(&mut || 0, &mut tee);
+        assert_eq!(tee.0, vec![1, 2]);
+    }
+
+    #[test]
+    fn fan_out_duplicates_to_every_output() {
+        let (mut a, mut b, mut c) = (vec![], VecDeque::new(), vec![]);
+        let mut computer = Computer::initialize(COUNT_TO_THREE);
+        computer.run_with_io(&mut || 0, &mut FanOut::new(vec![&mut a, &mut b, &mut c]));
+        assert_eq!(a, vec![1, 2, 3]);
+        assert_eq!(b, vec![1, 2, 3]);
+        assert_eq!(c, vec![1, 2, 3]);
+
+        let mut full = Bounded::new(0);
+        let mut computer = Computer::initialize(COUNT_TO_THREE);
+        let mut d = vec![];
+        computer.run_with_io(&mut || 0, &mut FanOut::new(vec![&mut d, &mut full]));
+        assert!(d.is_empty());
+    }
+
+    #[test]
+    fn text_streams() {
+        let mut computer = Computer::initialize(ECHO);
+        let mut input = TextInput::new("1, 2\n\n-3\n".as_bytes());
+        let mut output = TextOutput::new(vec![]);
+        computer.run_with_io(&mut input, &mut output);
+        assert_eq!(input.error(), None);
+        assert!(output.error().is_none());
+        assert_eq!(String::from_utf8(output.into_inner()).unwrap(), "1\n2\n-3\n");
+    }
+
+    #[test]
+    fn text_output_pauses_after_failed_write() {
+        // Only room for two outputs.
+        let mut buffer = [0u8; 4];
+        let mut computer = Computer::initialize(&[104, 1, 104, 2, 104, 3, 104, 4, 99]);
+        let mut output = TextOutput::new(&mut buffer[..]);
+        computer.run_with_io(&mut || 0, &mut output);
+        assert_eq!(output.error().map(|e| e.kind()), Some(io::ErrorKind::WriteZero));
+        assert!(!computer.is_halted());
+        assert_eq!(&buffer, b"1\n2\n");
+    }
+
+    #[test]
+    fn text_input_stops_at_invalid_value() {
+        let mut computer = Computer::initialize(ECHO);
+        let mut input = TextInput::new("1 2,x,3\n4\n".as_bytes());
+        let mut output = vec![];
+        computer.run_with_io(&mut input, &mut output);
+        assert_eq!(output, vec![1, 2]);
+        assert_eq!(input.error(), Some(&TextInputError::InvalidValue("x".to_string())));
+        assert_eq!(input.read_input(), None);
+    }
+
+    #[test]
+    fn frame_decoder_waits_for_complete_frames() {
+        let mut frames = FrameDecoder::new(3, |f: &[i64]| f.iter().sum::<i64>());
+        assert!(frames.is_empty());
+
+        frames.write_output(1);
+        frames.write_output(2);
+        assert_eq!(frames.next_frame(), None);
+        assert_eq!(frames.partial(), &[1, 2]);
+        assert!(!frames.is_empty());
+
+        frames.write_output(3);
+        assert_eq!(frames.partial(), &[] as &[i64]);
+        assert_eq!(frames.next_frame(), Some(6));
+        assert!(frames.is_empty());
+    }
+
+    #[test]
+    fn frame_decoder_drains_in_order() {
+        let mut computer = Computer::initialize(&[104, 1, 104, 2, 104, 3, 104, 4, 104, 5, 99]);
+        let mut frames = FrameDecoder::new(2, |f: &[i64]| (f[0], f[1]));
+        computer.run_with_io(&mut || 0, &mut frames);
+        assert_eq!(frames.drain().collect::<Vec<_>>(), vec![(1, 2), (3, 4)]);
+
+        // The partial frame outlives the drain.
+        assert!(!frames.is_empty());
+        assert_eq!(frames.partial(), &[5]);
+        frames.write_output(6);
+        assert_eq!(frames.drain().collect::<Vec<_>>(), vec![(5, 6)]);
+        assert!(frames.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Frame width must be positive!")]
+    fn frame_decoder_rejects_empty_frames() {
+        FrameDecoder::new(0, |f: &[i64]| f.len());
+    }
+}
@@ -0,0 +1,265 @@
+use std::cmp;
+use std::collections::HashSet;
+use std::fmt;
+
+use super::{Opcode, ParameterMode};
+
+/// A run of consecutive addresses whose values differ between two memories.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangedRange {
+    pub start: usize,
+    pub before: Vec<i64>,
+    pub after: Vec<i64>,
+}
+
+impl ChangedRange {
+    /// One past the last changed address.
+    pub fn end(&self) -> usize {
+        self.start + self.after.len()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryDiff {
+    pub ranges: Vec<ChangedRange>,
+}
+
+impl MemoryDiff {
+    /// Compares two memories address by address. Memory past the end of the shorter one reads
+    /// as 0, matching how the computer grows its memory.
+    pub fn between(before: &[i64], after: &[i64]) -> MemoryDiff {
+        let mut ranges: Vec<ChangedRange> = vec![];
+        for address in 0..cmp::max(before.len(), after.len()) {
+            let old = *before.get(address).unwrap_or(&0);
+            let new = *after.get(address).unwrap_or(&0);
+            if old == new {
+                continue;
+            }
+            match ranges.last_mut() {
+                Some(range) if range.end() == address => {
+                    range.before.push(old);
+                    range.after.push(new);
+                },
+                _ => ranges.push(ChangedRange { start: address, before: vec![old], after: vec![new] }),
+            }
+        }
+        MemoryDiff { ranges }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    pub fn changed_addresses(&self) -> impl Iterator<Item = usize> + '_ {
+        self.ranges.iter().flat_map(|r| r.start..r.end())
+    }
+}
+
+impl fmt::Display for MemoryDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for range in &self.ranges {
+            let before: Vec<String> = range.before.iter().map(|v| v.to_string()).collect();
+            let after: Vec<String> = range.after.iter().map(|v| v.to_string()).collect();
+            writeln!(f, "{:04}..{:04}: [{}] -> [{}]", range.start, range.end(), before.join(", "), after.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DisassembledInstruction {
+    pub address: usize,
+    pub len: usize,
+    pub text: String,
+}
+
+/// Decodes memory linearly from address 0. Values that don't form a valid instruction are
+/// shown as `DATA` and skipped one at a time.
+pub fn disassemble(memory: &[i64]) -> Vec<DisassembledInstruction> {
+    let mut instructions = vec![];
+    let mut address = 0;
+    while address < memory.len() {
+        let instruction =
+            decode(memory, address).unwrap_or_else(|| DisassembledInstruction {
+                address,
+                len: 1,
+                text: format!("DATA {}", memory[address]),
+            });
+        address += instruction.len;
+        instructions.push(instruction);
+    }
+    instructions
+}
+
+fn decode(memory: &[i64], address: usize) -> Option<DisassembledInstruction> {
+    let value = memory[address];
+    if value < 0 {
+        return None;
+    }
+    let opcode = Opcode::try_parse(value % 100)?;
+    let len = 1 + opcode.num_parameters();
+    if address + len > memory.len() {
+        return None;
+    }
+
+    let mut modes = value / 100;
+    let mut parameters = vec![];
+    for i in 1..len {
+        let parameter = memory[address + i];
+        parameters.push(match ParameterMode::try_of(modes % 10)? {
+            ParameterMode::Position => format!("[{}]", parameter),
+            ParameterMode::Immediate => parameter.to_string(),
+            ParameterMode::Relative => format!("[rb{:+}]", parameter),
+        });
+        modes /= 10;
+    }
+    if modes != 0 {
+        return None;
+    }
+
+    let text =
+        if parameters.is_empty() {
+            opcode.mnemonic().to_string()
+        } else {
+            format!("{} {}", opcode.mnemonic(), parameters.join(", "))
+        };
+    Some(DisassembledInstruction { address, len, text })
+}
+
+impl Opcode {
+    fn mnemonic(&self) -> &'static str {
+        match self {
+            Opcode::Add => "ADD",
+            Opcode::Mul => "MUL",
+            Opcode::Input => "IN",
+            Opcode::Output => "OUT",
+            Opcode::JumpIfTrue => "JT",
+            Opcode::JumpIfFalse => "JF",
+            Opcode::LessThan => "LT",
+            Opcode::Equals => "EQ",
+            Opcode::RelativeBaseOffset => "ARB",
+            Opcode::Halt => "HALT",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DumpOptions {
+    /// Values per row.
+    pub width: usize,
+    pub start: usize,
+    /// One past the last address shown; the end of memory when `None`.
+    pub end: Option<usize>,
+    /// Lists the instructions starting in each row alongside it.
+    pub disassemble: bool,
+    /// Marks values that differ in this diff with `*`.
+    pub highlight: Option<MemoryDiff>,
+}
+
+impl Default for DumpOptions {
+    fn default() -> DumpOptions {
+        DumpOptions { width: 8, start: 0, end: None, disassemble: false, highlight: None }
+    }
+}
+
+/// Formats memory as rows of values prefixed by the address of the first value in the row.
+pub fn dump(memory: &[i64], options: &DumpOptions) -> String {
+    let width = cmp::max(options.width, 1);
+    let end = cmp::min(options.end.unwrap_or(memory.len()), memory.len());
+    let start = options.start - options.start % width;
+    let cell_width = memory.iter().map(|v| v.to_string().len()).max().unwrap_or(1);
+    let instructions = if options.disassemble { disassemble(memory) } else { vec![] };
+    let changed: HashSet<usize> =
+        options.highlight.as_ref().map(|d| d.changed_addresses().collect()).unwrap_or_default();
+
+    let mut result = String::new();
+    let mut row_start = start;
+    while row_start < end {
+        let row_end = cmp::min(row_start + width, end);
+        result.push_str(&format!("{:04}:", row_start));
+        for address in row_start..row_start + width {
+            match memory.get(address).filter(|_| address >= options.start && address < row_end) {
+                Some(value) => {
+                    let marker = if changed.contains(&address) { '*' } else { ' ' };
+                    result.push_str(&format!(" {}{:>w$}", marker, value, w = cell_width));
+                },
+                None => result.push_str(&" ".repeat(cell_width + 2)),
+            }
+        }
+
+        let overlay: Vec<&str> =
+            instructions.iter()
+                .filter(|i| i.address >= cmp::max(row_start, options.start) && i.address < row_end)
+                .map(|i| i.text.as_str())
+                .collect();
+        if !overlay.is_empty() {
+            result.push_str("  | ");
+            result.push_str(&overlay.join("; "));
+        }
+        result.push('\n');
+        row_start += width;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diffs_memory() {
+        let diff = MemoryDiff::between(&[1, 2, 3, 4, 5], &[1, 9, 9, 4, 6, 7]);
+        assert_eq!(diff.ranges, vec![
+            ChangedRange { start: 1, before: vec![2, 3], after: vec![9, 9] },
+            ChangedRange { start: 4, before: vec![5, 0], after: vec![6, 7] },
+        ]);
+        assert_eq!(diff.changed_addresses().collect::<Vec<_>>(), vec![1, 2, 4, 5]);
+        assert_eq!(diff.to_string(), "0001..0003: [2, 3] -> [9, 9]\n0004..0006: [5, 0] -> [6, 7]\n");
+
+        assert!(MemoryDiff::between(&[1, 0], &[1]).is_empty());
+    }
+
+    #[test]
+    fn disassembles_instructions_and_data() {
+        let listing: Vec<(usize, String)> =
+            disassemble(&[1002, 4, 3, 4, 109, -2, 21101, 1, 2, 0, 99, 12345, -1, 3])
+                .into_iter()
+                .map(|i| (i.address, i.text))
+                .collect();
+        assert_eq!(listing, vec![
+            (0, "MUL [4], 3, [4]".to_string()),
+            (4, "ARB -2".to_string()),
+            (6, "ADD 1, 2, [rb+0]".to_string()),
+            (10, "HALT".to_string()),
+            (11, "DATA 12345".to_string()),
+            (12, "DATA -1".to_string()),
+            (13, "DATA 3".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn dumps_rows() {
+        let memory = [1, 0, 0, 3, 99, 30, 40, 50];
+        assert_eq!(
+            dump(&memory, &DumpOptions { width: 4, ..DumpOptions::default() }),
+            "0000:   1   0   0   3\n0004:  99  30  40  50\n");
+
+        let highlight = MemoryDiff::between(&memory, &[1, 0, 0, 3, 99, 30, 70, 50]);
+        let options = DumpOptions { width: 3, start: 4, end: Some(7), highlight: Some(highlight), ..DumpOptions::default() };
+        assert_eq!(dump(&memory, &options), "0003:      99  30\n0006: *40        \n");
+    }
+
+    #[test]
+    fn dump_overlays_instructions_in_range() {
+        let memory = [1101, 1, 2, 7, 104, 7, 99, 0];
+        let options = DumpOptions { width: 4, start: 5, disassemble: true, ..DumpOptions::default() };
+        assert_eq!(
+            dump(&memory, &options),
+            "0004:           7    99     0  | HALT; DATA 0\n");
+
+        let options = DumpOptions { width: 4, disassemble: true, ..DumpOptions::default() };
+        assert_eq!(
+            dump(&memory, &options),
+            "0000:  1101     1     2     7  | ADD 1, 2, [7]\n0004:   104     7    99     0  | OUT 7; HALT; DATA 0\n");
+    }
+}
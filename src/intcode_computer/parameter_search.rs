@@ -0,0 +1,289 @@
+use std::panic;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+/// Every candidate parameter list to try, in a fixed order. Candidates are built from their
+/// index when asked for, so large spaces cost nothing up front.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParameterSpace {
+    /// One value per dimension, varying the last dimension fastest.
+    Product(Vec<Vec<i64>>),
+    /// Orderings of the values, in lexicographic order of their positions.
+    Permutations(Vec<i64>),
+}
+
+impl ParameterSpace {
+    /// One parameter taking every value in `values`.
+    pub fn range<I: IntoIterator<Item = i64>>(values: I) -> ParameterSpace {
+        ParameterSpace::Product(vec![values.into_iter().collect()])
+    }
+
+    /// Every ordering of `values`.
+    pub fn permutations(values: &[i64]) -> ParameterSpace {
+        ParameterSpace::Permutations(values.to_vec())
+    }
+
+    /// Every combination of one value per dimension, varying the last dimension fastest.
+    pub fn product(dimensions: &[Vec<i64>]) -> ParameterSpace {
+        ParameterSpace::Product(dimensions.to_vec())
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            ParameterSpace::Product(dimensions) => dimensions.iter().map(|d| d.len()).product(),
+            ParameterSpace::Permutations(values) => (1..=values.len()).product(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The candidate at `index`, which must be less than `len()`.
+    pub fn candidate(&self, mut index: usize) -> Vec<i64> {
+        match self {
+            ParameterSpace::Product(dimensions) => {
+                let mut candidate = vec![0; dimensions.len()];
+                for (value, dimension) in candidate.iter_mut().zip(dimensions.iter()).rev() {
+                    *value = dimension[index % dimension.len()];
+                    index /= dimension.len();
+                }
+                candidate
+            },
+            ParameterSpace::Permutations(values) => {
+                // Each position picks from what's left, in blocks the size of the number of
+                // orderings of the rest.
+                let mut remaining = values.clone();
+                let mut block: usize = (1..values.len()).product();
+                let mut candidate = Vec::with_capacity(values.len());
+                while !remaining.is_empty() {
+                    candidate.push(remaining.remove(index / block));
+                    index %= block;
+                    block /= remaining.len().max(1);
+                }
+                candidate
+            },
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Vec<i64>> + '_ {
+        (0..self.len()).map(move |i| self.candidate(i))
+    }
+}
+
+/// Evaluates a program over a parameter space on several threads.
+///
+/// `setup` builds whatever needs to run for a candidate (usually a patched `Computer`), and
+/// may borrow the program to do so; `objective` runs it and scores the result. Results are
+/// deterministic: ties and matches resolve to the earliest candidate in the space.
+///
+/// A panic in `setup` or `objective` aborts the whole search: once the other threads have
+/// worked through their share, the method that started the search panics with the same
+/// payload. Candidates that are expected to fail, such as programs that may crash, should
+/// report that through the result instead.
+pub struct ParameterSearch<'a, S, O> {
+    program: &'a [i64],
+    space: ParameterSpace,
+    setup: S,
+    objective: O,
+    threads: usize,
+}
+
+impl<'a, C, T, S, O> ParameterSearch<'a, S, O>
+where
+    S: Fn(&'a [i64], &[i64]) -> C + Sync,
+    O: Fn(C) -> T + Sync,
+    T: Send,
+{
+    pub fn new(program: &'a [i64], space: ParameterSpace, setup: S, objective: O) -> ParameterSearch<'a, S, O> {
+        let threads = thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+        ParameterSearch { program, space, setup, objective, threads }
+    }
+
+    pub fn threads(mut self, threads: usize) -> ParameterSearch<'a, S, O> {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Runs `work` once per thread with the thread's offset into the space, waiting for all of
+    /// them. Panics from any of them are raised again here, keeping their payload.
+    fn on_threads<W: Fn(usize) + Sync>(&self, work: W) {
+        thread::scope(|scope| {
+            let work = &work;
+            let handles: Vec<_> = (0..self.threads).map(|offset| scope.spawn(move || work(offset))).collect();
+            for handle in handles {
+                if let Err(payload) = handle.join() {
+                    panic::resume_unwind(payload);
+                }
+            }
+        });
+    }
+
+    fn evaluate(&self, candidate: &[i64]) -> T {
+        (self.objective)((self.setup)(self.program, candidate))
+    }
+
+    /// The earliest candidate whose result satisfies `predicate`. Threads stop as soon as
+    /// every candidate before a known match has been checked.
+    pub fn find_first<P>(&self, predicate: P) -> Option<(Vec<i64>, T)>
+    where
+        P: Fn(&T) -> bool + Sync,
+    {
+        let found = AtomicUsize::new(usize::MAX);
+        let result = Mutex::new(None);
+
+        self.on_threads(|offset| {
+            for i in (offset..self.space.len()).step_by(self.threads) {
+                if i > found.load(Ordering::SeqCst) {
+                    return;
+                }
+                let candidate = self.space.candidate(i);
+                let value = self.evaluate(&candidate);
+                if predicate(&value) {
+                    let mut result = result.lock().unwrap();
+                    if i < found.load(Ordering::SeqCst) {
+                        found.store(i, Ordering::SeqCst);
+                        *result = Some((candidate, value));
+                    }
+                    return;
+                }
+            }
+        });
+
+        result.into_inner().unwrap()
+    }
+
+    /// The candidate with the greatest result.
+    pub fn maximize(&self) -> Option<(Vec<i64>, T)>
+    where
+        T: Ord,
+    {
+        self.best_by(|a, b| a > b)
+    }
+
+    /// The candidate with the least result.
+    pub fn minimize(&self) -> Option<(Vec<i64>, T)>
+    where
+        T: Ord,
+    {
+        self.best_by(|a, b| a < b)
+    }
+
+    fn best_by<B>(&self, better: B) -> Option<(Vec<i64>, T)>
+    where
+        B: Fn(&T, &T) -> bool + Sync,
+    {
+        let best: Mutex<Option<(usize, T)>> = Mutex::new(None);
+
+        self.on_threads(|offset| {
+            for i in (offset..self.space.len()).step_by(self.threads) {
+                let value = self.evaluate(&self.space.candidate(i));
+                let mut best = best.lock().unwrap();
+                let replace = match &*best {
+                    None => true,
+                    Some((j, current)) => better(&value, current) || (!better(current, &value) && i < *j),
+                };
+                if replace {
+                    *best = Some((i, value));
+                }
+            }
+        });
+
+        best.into_inner().unwrap().map(|(i, value)| (self.space.candidate(i), value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode_computer::Computer;
+    use itertools::Itertools;
+
+    // Outputs 10 * [5] - [6], with the parameters patched into addresses 5 and 6.
+    const PROGRAM: &[i64] = &[1002, 5, 10, 5, 99, 0, 0];
+
+    fn patched(program: &[i64], candidate: &[i64]) -> Computer {
+        let mut computer = Computer::initialize(program);
+        *computer.access(5) = candidate[0];
+        *computer.access(6) = candidate[1];
+        computer
+    }
+
+    fn result(mut computer: Computer) -> i64 {
+        computer.run();
+        *computer.access(5) - *computer.access(6)
+    }
+
+    #[test]
+    fn spaces_list_candidates_in_order() {
+        assert_eq!(ParameterSpace::range(3..6).iter().collect::<Vec<_>>(), vec![vec![3], vec![4], vec![5]]);
+
+        let product = ParameterSpace::product(&[vec![1, 2], vec![], vec![3]]);
+        assert!(product.is_empty());
+        let product = ParameterSpace::product(&[vec![1, 2], vec![3, 4, 5]]);
+        assert_eq!(product.len(), 6);
+        assert_eq!(product.iter().collect::<Vec<_>>(), vec![vec![1, 3], vec![1, 4], vec![1, 5], vec![2, 3], vec![2, 4], vec![2, 5]]);
+
+        for n in 0..=5 {
+            let values: Vec<i64> = (10..10 + n).collect();
+            let space = ParameterSpace::permutations(&values);
+            let expected: Vec<Vec<i64>> = values.iter().cloned().permutations(values.len()).collect();
+            assert_eq!(space.len(), expected.len());
+            assert_eq!(space.iter().collect::<Vec<_>>(), expected);
+        }
+    }
+
+    #[test]
+    fn maximizes_and_minimizes() {
+        let space = ParameterSpace::product(&[vec![1, 2, 3], vec![4, 5]]);
+        assert_eq!(ParameterSearch::new(PROGRAM, space.clone(), patched, result).maximize(), Some((vec![3, 4], 26)));
+        assert_eq!(ParameterSearch::new(PROGRAM, space, patched, result).minimize(), Some((vec![1, 5], 5)));
+        assert_eq!(ParameterSearch::new(PROGRAM, ParameterSpace::product(&[vec![], vec![1]]), patched, result).maximize(), None);
+    }
+
+    #[test]
+    fn ties_resolve_to_the_earliest_candidate() {
+        // 10a - b is 20 for (2, 0), (3, 10) and (4, 20).
+        let space = ParameterSpace::product(&[vec![4, 3, 2, 1], vec![20, 10, 0]]);
+        for threads in 1..=8 {
+            let search = ParameterSearch::new(PROGRAM, space.clone(), patched, result).threads(threads);
+            assert_eq!(search.find_first(|v| *v == 20), Some((vec![4, 20], 20)), "{} threads", threads);
+            assert_eq!(search.maximize(), Some((vec![4, 0], 40)), "{} threads", threads);
+            assert_eq!(search.minimize(), Some((vec![1, 20], -10)), "{} threads", threads);
+        }
+    }
+
+    #[test]
+    fn find_first_stops_early() {
+        let evaluated = AtomicUsize::new(0);
+        let search =
+            ParameterSearch::new(
+                PROGRAM,
+                ParameterSpace::range(0..1000),
+                |_, candidate| candidate[0],
+                |value| {
+                    evaluated.fetch_add(1, Ordering::SeqCst);
+                    value
+                },
+            )
+            .threads(1);
+        assert_eq!(search.find_first(|v| *v == 10), Some((vec![10], 10)));
+        assert_eq!(evaluated.load(Ordering::SeqCst), 11);
+
+        assert_eq!(search.find_first(|v| *v < 0), None);
+        assert_eq!(evaluated.load(Ordering::SeqCst), 1011);
+    }
+
+    #[test]
+    #[should_panic(expected = "Bad candidate!")]
+    fn panicking_candidate_aborts_search() {
+        let search = ParameterSearch::new(
+            PROGRAM,
+            ParameterSpace::range(0..8),
+            |_, candidate| candidate[0],
+            |value| if value == 5 { panic!("Bad candidate!") } else { value },
+        );
+        search.threads(2).maximize();
+    }
+}
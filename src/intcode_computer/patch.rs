@@ -0,0 +1,279 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// A set of memory assignments applied to a program before it runs.
+///
+/// Written as comma separated `address=value` pairs, optionally prefixed by a name:
+/// `alarm: 1=12, 2=2`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Patch {
+    name: Option<String>,
+    assignments: Vec<(usize, i64)>,
+}
+
+impl Patch {
+    pub fn new() -> Patch {
+        Patch { name: None, assignments: vec![] }
+    }
+
+    pub fn named(name: &str) -> Patch {
+        Patch { name: Some(name.to_string()), assignments: vec![] }
+    }
+
+    pub fn set(mut self, address: usize, value: i64) -> Patch {
+        self.assignments.push((address, value));
+        self
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn assignments(&self) -> &[(usize, i64)] {
+        &self.assignments
+    }
+
+    pub fn parse(spec: &str) -> Result<Patch, PatchError> {
+        parse_line(spec, 1)
+    }
+
+    /// Applies the patch to `memory`, returning what was changed. Nothing is written unless
+    /// every address exists and no address is set to two different values.
+    pub fn apply(&self, memory: &mut [i64]) -> Result<Vec<AppliedPatch>, PatchError> {
+        self.check_conflicts(&[])?;
+        if let Some((address, _)) = self.assignments.iter().find(|(a, _)| *a >= memory.len()) {
+            return Err(PatchError::AddressOutOfRange { address: *address, size: memory.len() });
+        }
+
+        Ok(self.assignments.iter()
+            .map(|(address, value)| {
+                let applied = AppliedPatch {
+                    name: self.name.clone(),
+                    address: *address,
+                    previous: memory[*address],
+                    value: *value,
+                };
+                memory[*address] = *value;
+                applied
+            })
+            .collect())
+    }
+
+    /// Fails if this patch sets an address to a different value than another of its own
+    /// assignments or one of the `earlier` ones.
+    pub fn check_conflicts(&self, earlier: &[AppliedPatch]) -> Result<(), PatchError> {
+        let mut values: HashMap<usize, i64> = earlier.iter().map(|a| (a.address, a.value)).collect();
+        for (address, value) in &self.assignments {
+            match values.insert(*address, *value) {
+                Some(first) if first != *value =>
+                    return Err(PatchError::ConflictingWrite { address: *address, first, second: *value }),
+                _ => {},
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for Patch {
+    fn default() -> Patch {
+        Patch::new()
+    }
+}
+
+impl fmt::Display for Patch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(name) = &self.name {
+            write!(f, "{}: ", name)?;
+        }
+        let assignments: Vec<String> = self.assignments.iter().map(|(a, v)| format!("{}={}", a, v)).collect();
+        write!(f, "{}", assignments.join(", "))
+    }
+}
+
+/// Record of a single assignment made by a patch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppliedPatch {
+    pub name: Option<String>,
+    pub address: usize,
+    pub previous: i64,
+    pub value: i64,
+}
+
+impl fmt::Display for AppliedPatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}: {} -> {}", self.name.as_ref().map(|n| format!("{} ", n)).unwrap_or_default(), self.address, self.previous, self.value)
+    }
+}
+
+/// Named patches, one per line. Blank lines and lines starting with `#` are ignored.
+#[derive(Debug, Clone, Default)]
+pub struct PatchSet {
+    patches: HashMap<String, Patch>,
+}
+
+impl PatchSet {
+    pub fn parse(input: &str) -> Result<PatchSet, PatchError> {
+        let mut patches = HashMap::new();
+        for (i, line) in input.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let patch = parse_line(line, i + 1)?;
+            let name = match patch.name() {
+                Some(name) => name.to_string(),
+                None => return Err(PatchError::Syntax { line: i + 1, text: line.to_string() }),
+            };
+            if patches.insert(name.clone(), patch).is_some() {
+                return Err(PatchError::DuplicatePatch { line: i + 1, name });
+            }
+        }
+        Ok(PatchSet { patches })
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<PatchSet, PatchError> {
+        let input = fs::read_to_string(path.as_ref())
+            .map_err(|e| PatchError::Io(format!("{}: {}", path.as_ref().display(), e)))?;
+        PatchSet::parse(&input)
+    }
+
+    pub fn get(&self, name: &str) -> Result<&Patch, PatchError> {
+        self.patches.get(name).ok_or_else(|| PatchError::UnknownPatch(name.to_string()))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatchError {
+    Syntax { line: usize, text: String },
+    AddressOutOfRange { address: usize, size: usize },
+    ConflictingWrite { address: usize, first: i64, second: i64 },
+    DuplicatePatch { line: usize, name: String },
+    UnknownPatch(String),
+    Io(String),
+}
+
+impl fmt::Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PatchError::Syntax { line, text } => write!(f, "Invalid patch on line {}: {}", line, text),
+            PatchError::AddressOutOfRange { address, size } =>
+                write!(f, "Patch address {} is outside the program ({} values)", address, size),
+            PatchError::ConflictingWrite { address, first, second } =>
+                write!(f, "Address {} is patched to both {} and {}", address, first, second),
+            PatchError::DuplicatePatch { line, name } => write!(f, "Patch {} is defined again on line {}", name, line),
+            PatchError::UnknownPatch(name) => write!(f, "Unknown patch: {}", name),
+            PatchError::Io(message) => write!(f, "Could not read patches: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for PatchError {}
+
+fn parse_line(line: &str, line_number: usize) -> Result<Patch, PatchError> {
+    let syntax_error = || PatchError::Syntax { line: line_number, text: line.to_string() };
+
+    let (name, spec) = match line.find(':') {
+        Some(i) => (Some(line[..i].trim()), &line[i + 1..]),
+        None => (None, line),
+    };
+    if name == Some("") {
+        return Err(syntax_error());
+    }
+
+    let mut patch = Patch { name: name.map(|n| n.to_string()), assignments: vec![] };
+    for assignment in spec.split(',') {
+        let parts: Vec<&str> = assignment.split('=').map(|p| p.trim()).collect();
+        if parts.len() != 2 {
+            return Err(syntax_error());
+        }
+        let address = parts[0].parse().map_err(|_| syntax_error())?;
+        let value = parts[1].parse().map_err(|_| syntax_error())?;
+        patch.assignments.push((address, value));
+    }
+    Ok(patch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode_computer::Computer;
+    use crate::test_support::TempFile;
+
+    #[test]
+    fn parses_patches() {
+        let patch = Patch::parse("alarm: 1=12, 2 = -2").unwrap();
+        assert_eq!(patch, Patch::named("alarm").set(1, 12).set(2, -2));
+        assert_eq!(patch.to_string(), "alarm: 1=12, 2=-2");
+        assert_eq!(Patch::parse("0=2").unwrap(), Patch::new().set(0, 2));
+
+        for invalid in &["1=2=3", "1=x", "-1=2", ": 1=2", "alarm: ", "1=2,"] {
+            assert_eq!(
+                Patch::parse(invalid),
+                Err(PatchError::Syntax { line: 1, text: invalid.to_string() }),
+                "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn applies_and_records_assignments() {
+        let mut memory = vec![1, 0, 0, 0, 99];
+        let applied = Patch::named("alarm").set(1, 4).set(2, 4).apply(&mut memory).unwrap();
+        assert_eq!(memory, vec![1, 4, 4, 0, 99]);
+        assert_eq!(applied[0], AppliedPatch { name: Some("alarm".to_string()), address: 1, previous: 0, value: 4 });
+        assert_eq!(applied[1].to_string(), "alarm 2: 0 -> 4");
+    }
+
+    #[test]
+    fn rejects_out_of_range_addresses_without_writing() {
+        let mut memory = vec![1, 0, 0, 0, 99];
+        assert_eq!(
+            Patch::new().set(1, 4).set(5, 4).apply(&mut memory),
+            Err(PatchError::AddressOutOfRange { address: 5, size: 5 }));
+        assert_eq!(memory, vec![1, 0, 0, 0, 99]);
+    }
+
+    #[test]
+    fn rejects_conflicting_writes() {
+        let mut memory = vec![0; 4];
+        assert_eq!(
+            Patch::new().set(1, 4).set(1, 5).apply(&mut memory),
+            Err(PatchError::ConflictingWrite { address: 1, first: 4, second: 5 }));
+        assert_eq!(memory, vec![0; 4]);
+        assert!(Patch::new().set(1, 4).set(1, 4).apply(&mut memory).is_ok());
+
+        let (a, b, c) = (Patch::named("a").set(0, 1), Patch::named("b").set(0, 2), Patch::named("c").set(0, 1).set(3, 3));
+        assert_eq!(
+            Computer::initialize_patched(&[0; 4], &[&a, &b]).err(),
+            Some(PatchError::ConflictingWrite { address: 0, first: 1, second: 2 }));
+        let computer = Computer::initialize_patched(&[0; 4], &[&a, &c]).unwrap();
+        assert_eq!(computer.memory(), &[1, 0, 0, 3]);
+        assert_eq!(computer.applied_patches().len(), 3);
+    }
+
+    #[test]
+    fn parses_patch_sets() {
+        let set = PatchSet::parse("# Day 2\nalarm: 1=12, 2=2\n\nfree play: 0=2\n").unwrap();
+        assert_eq!(set.get("alarm"), Ok(&Patch::named("alarm").set(1, 12).set(2, 2)));
+        assert_eq!(set.get("free play"), Ok(&Patch::named("free play").set(0, 2)));
+        assert_eq!(set.get("cheat"), Err(PatchError::UnknownPatch("cheat".to_string())));
+
+        assert_eq!(PatchSet::parse("a: 0=1\n1=2").unwrap_err(), PatchError::Syntax { line: 2, text: "1=2".to_string() });
+        assert_eq!(
+            PatchSet::parse("a: 0=1\nb: 0=2\na: 1=1").unwrap_err(),
+            PatchError::DuplicatePatch { line: 3, name: "a".to_string() });
+    }
+
+    #[test]
+    fn loads_patch_sets() {
+        let file = TempFile::new("patches.txt");
+        fs::write(file.path(), "alarm: 1=12, 2=2\n").unwrap();
+        let set = PatchSet::load(file.path());
+        assert_eq!(set.unwrap().get("alarm").unwrap().assignments(), &[(1, 12), (2, 2)]);
+
+        let path = file.path().to_path_buf();
+        drop(file);
+        assert!(matches!(PatchSet::load(&path), Err(PatchError::Io(_))));
+    }
+}
@@ -0,0 +1,312 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use super::{Computer, Input, Output};
+
+/// One value crossing the computer's I/O boundary, or the computer halting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    Input(i64),
+    Output(i64),
+    Halt,
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Event::Input(value) => write!(f, "in {}", value),
+            Event::Output(value) => write!(f, "out {}", value),
+            Event::Halt => write!(f, "halt"),
+        }
+    }
+}
+
+/// Everything a computer read and wrote during a run, in order.
+///
+/// Saved one event per line as `in <value>`, `out <value>` or `halt`. Blank lines and lines
+/// starting with `#` are ignored.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Session {
+    events: Vec<Event>,
+}
+
+impl Session {
+    pub fn new() -> Session {
+        Session::default()
+    }
+
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    pub fn inputs(&self) -> impl Iterator<Item = i64> + '_ {
+        self.events.iter().filter_map(|e| match e {
+            Event::Input(value) => Some(*value),
+            _ => None,
+        })
+    }
+
+    pub fn outputs(&self) -> impl Iterator<Item = i64> + '_ {
+        self.events.iter().filter_map(|e| match e {
+            Event::Output(value) => Some(*value),
+            _ => None,
+        })
+    }
+
+    /// Runs `computer` like `run_with_io`, appending every value it reads or writes. Can be
+    /// called repeatedly as the computer pauses for input; halting is recorded once.
+    pub fn record<I: Input, O: Output>(&mut self, computer: &mut Computer, input: &mut I, output: &mut O) {
+        let events = RefCell::new(std::mem::take(&mut self.events));
+        computer.run_with_io(
+            &mut RecordedInput { inner: input, events: &events },
+            &mut RecordedOutput { inner: output, events: &events });
+        self.events = events.into_inner();
+
+        if computer.is_halted() && self.events.last() != Some(&Event::Halt) {
+            self.events.push(Event::Halt);
+        }
+    }
+
+    /// Runs `computer` with the recorded inputs, checking that it produces the recorded
+    /// outputs. Stops at the first difference, or once the session is used up and the
+    /// computer wants more input.
+    pub fn replay(&self, computer: &mut Computer) -> Result<(), Divergence> {
+        let state = RefCell::new(Replay { events: &self.events, position: 0, divergence: None });
+        computer.run_with_io(&mut ReplayInput(&state), &mut ReplayOutput(&state));
+
+        let state = state.into_inner();
+        if let Some(divergence) = state.divergence {
+            return Err(divergence);
+        }
+        if computer.is_halted() && state.expected() != Some(Event::Halt) {
+            return Err(Divergence { step: state.step(), expected: state.expected(), found: Activity::Halt });
+        }
+        Ok(())
+    }
+
+    pub fn parse(input: &str) -> Result<Session, SessionError> {
+        let mut events = vec![];
+        for (i, line) in input.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let syntax_error = || SessionError::Syntax { line: i + 1, text: line.to_string() };
+            let event = match line.split_whitespace().collect::<Vec<_>>().as_slice() {
+                ["in", value] => Event::Input(value.parse().map_err(|_| syntax_error())?),
+                ["out", value] => Event::Output(value.parse().map_err(|_| syntax_error())?),
+                ["halt"] => Event::Halt,
+                _ => return Err(syntax_error()),
+            };
+            events.push(event);
+        }
+        Ok(Session { events })
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Session, SessionError> {
+        let input = fs::read_to_string(path.as_ref())
+            .map_err(|e| SessionError::Io(format!("{}: {}", path.as_ref().display(), e)))?;
+        Session::parse(&input)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), SessionError> {
+        fs::write(path.as_ref(), self.to_string())
+            .map_err(|e| SessionError::Io(format!("{}: {}", path.as_ref().display(), e)))
+    }
+}
+
+impl fmt::Display for Session {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for event in &self.events {
+            writeln!(f, "{}", event)?;
+        }
+        Ok(())
+    }
+}
+
+/// Where a replayed run first differed from its session. Steps count events from 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Divergence {
+    pub step: usize,
+    /// The recorded event, or `None` if the session had already ended.
+    pub expected: Option<Event>,
+    pub found: Activity,
+}
+
+/// What a replayed computer did instead of the recorded event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Activity {
+    InputRequest,
+    Output(i64),
+    Halt,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let found = match self.found {
+            Activity::InputRequest => "an input request".to_string(),
+            Activity::Output(value) => format!("`out {}`", value),
+            Activity::Halt => "`halt`".to_string(),
+        };
+        match self.expected {
+            Some(expected) => write!(f, "Diverged at step {}: expected `{}`, found {}", self.step, expected, found),
+            None => write!(f, "Diverged at step {}: session ended, found {}", self.step, found),
+        }
+    }
+}
+
+impl std::error::Error for Divergence {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionError {
+    Syntax { line: usize, text: String },
+    Io(String),
+}
+
+impl fmt::Display for SessionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SessionError::Syntax { line, text } => write!(f, "Invalid session event on line {}: {}", line, text),
+            SessionError::Io(message) => write!(f, "Could not access session: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for SessionError {}
+
+struct RecordedInput<'a, I> {
+    inner: &'a mut I,
+    events: &'a RefCell<Vec<Event>>,
+}
+
+impl<'a, I: Input> Input for RecordedInput<'a, I> {
+    fn read_input(&mut self) -> Option<i64> {
+        let value = self.inner.read_input();
+        if let Some(value) = value {
+            self.events.borrow_mut().push(Event::Input(value));
+        }
+        value
+    }
+}
+
+struct RecordedOutput<'a, O> {
+    inner: &'a mut O,
+    events: &'a RefCell<Vec<Event>>,
+}
+
+impl<'a, O: Output> Output for RecordedOutput<'a, O> {
+    fn write_output(&mut self, output: i64) {
+        self.events.borrow_mut().push(Event::Output(output));
+        self.inner.write_output(output);
+    }
+
+    fn is_ready(&self) -> bool {
+        self.inner.is_ready()
+    }
+}
+
+struct Replay<'a> {
+    events: &'a [Event],
+    position: usize,
+    divergence: Option<Divergence>,
+}
+
+impl<'a> Replay<'a> {
+    fn expected(&self) -> Option<Event> {
+        self.events.get(self.position).cloned()
+    }
+
+    fn step(&self) -> usize {
+        self.position + 1
+    }
+
+    fn diverge(&mut self, found: Activity) {
+        self.divergence = Some(Divergence { step: self.step(), expected: self.expected(), found });
+    }
+}
+
+struct ReplayInput<'s, 'a>(&'s RefCell<Replay<'a>>);
+
+impl<'s, 'a> Input for ReplayInput<'s, 'a> {
+    fn read_input(&mut self) -> Option<i64> {
+        let mut replay = self.0.borrow_mut();
+        if replay.divergence.is_some() {
+            return None;
+        }
+        match replay.expected() {
+            Some(Event::Input(value)) => {
+                replay.position += 1;
+                Some(value)
+            },
+            None => None,
+            Some(_) => {
+                replay.diverge(Activity::InputRequest);
+                None
+            },
+        }
+    }
+}
+
+struct ReplayOutput<'s, 'a>(&'s RefCell<Replay<'a>>);
+
+impl<'s, 'a> Output for ReplayOutput<'s, 'a> {
+    fn write_output(&mut self, output: i64) {
+        let mut replay = self.0.borrow_mut();
+        if replay.expected() == Some(Event::Output(output)) {
+            replay.position += 1;
+        } else {
+            replay.diverge(Activity::Output(output));
+        }
+    }
+
+    fn is_ready(&self) -> bool {
+        self.0.borrow().divergence.is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+    use crate::test_support::TempFile;
+
+    // Doubles one input, then halts.
+    const DOUBLE: &[i64] = &[3, 9, 1002, 9, 2, 9, 4, 9, 99, 0];
+
+    fn recorded(input: i64) -> Session {
+        let mut session = Session::new();
+        session.record(&mut Computer::initialize(DOUBLE), &mut VecDeque::from(vec![input]), &mut vec![]);
+        session
+    }
+
+    #[test]
+    fn records_events() {
+        let session = recorded(21);
+        assert_eq!(session.events(), &[Event::Input(21), Event::Output(42), Event::Halt]);
+        assert_eq!(session.to_string(), "in 21\nout 42\nhalt\n");
+        assert_eq!(session.replay(&mut Computer::initialize(DOUBLE)), Ok(()));
+    }
+
+    #[test]
+    fn saves_and_loads() {
+        let file = TempFile::new("session.txt");
+        let session = recorded(-5);
+        session.save(file.path()).unwrap();
+        let loaded = Session::load(file.path());
+
+        assert_eq!(loaded, Ok(session));
+        assert_eq!(loaded.unwrap().replay(&mut Computer::initialize(DOUBLE)), Ok(()));
+    }
+
+    #[test]
+    fn parses_saved_format() {
+        let session = Session::parse("# comment\n\nin 1\n  out 2\nhalt").unwrap();
+        assert_eq!(session, recorded(1));
+        assert_eq!(
+            Session::parse("in 1\nout x"),
+            Err(SessionError::Syntax { line: 2, text: "out x".to_string() }));
+        assert!(matches!(Session::load("/nonexistent/session.txt"), Err(SessionError::Io(_))));
+    }
+}
@@ -2,7 +2,16 @@ extern crate aoc_runner;
 #[macro_use]
 extern crate aoc_runner_derive;
 
-mod intcode_computer;
+pub mod grid;
+pub mod intcode_computer;
+pub mod ocr;
+pub mod parse;
+pub mod search;
+pub mod solutions;
+pub mod tools;
+
+#[cfg(test)]
+mod test_support;
 
 mod day1;
 mod day2;
@@ -17,8 +26,18 @@ mod day10;
 mod day11;
 mod day12;
 mod day13;
+mod day19;
 mod day20;
+mod day21;
 mod day22;
 mod day23;
 
+pub use day12::System;
+
+/// Internals that the benchmarks in `benches/` measure directly. Not part of the public API.
+#[doc(hidden)]
+pub mod bench_support {
+    pub use crate::day23::Network;
+}
+
 aoc_lib!{ year = 2019 }
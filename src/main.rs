@@ -0,0 +1,324 @@
+//! Runs the solutions without `cargo aoc`:
+//!
+//! ```text
+//! cargo run --release -- [DAY[.PART]]... [--input PATH | --input -] [--input-dir DIR]
+//! ```
+//!
+//! With no days given, runs everything. Each day reads `input/2019/dayN.txt` (or `dayN.txt`
+//! under `--input-dir`), unless `--input` names a file, or `-` for stdin, which only works when
+//! a single day is selected. Exits non-zero if any input is missing or any solution fails.
+//!
+//! With `--verify`, also checks each answer against the regression snapshot in `snapshot.txt`
+//! next to the inputs (or the file given with `--snapshot`), failing on any change.
+//!
+//! Instead of solving, `--export DAY PATH` writes a picture of the day's puzzle to `PATH`,
+//! `--show DAY` draws it in the terminal, `--play DAY` plays its game with moves read from
+//! stdin, and `--record DAY PATH` saves the Intcode I/O of a run to `PATH`.
+
+use aoc2019::solutions::{Solution, SOLUTIONS};
+use aoc2019::tools;
+use std::collections::HashMap;
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::io::{self, Read};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::process;
+use std::time::{Duration, Instant};
+
+const USAGE: &str =
+    "usage: aoc2019 [DAY[.PART]]... [--input PATH | --input -] [--input-dir DIR] [--verify [--snapshot PATH]]\n       aoc2019 (--export DAY PATH | --show DAY | --play DAY | --record DAY PATH) [--input PATH | --input -] [--input-dir DIR]";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Selection {
+    day: u32,
+    part: Option<u32>,
+}
+
+impl Selection {
+    fn parse(arg: &str) -> Option<Selection> {
+        let mut pieces = arg.splitn(2, '.');
+        let day = pieces.next()?.parse().ok()?;
+        let part = match pieces.next() {
+            Some(part) => Some(part.parse().ok()?),
+            None => None,
+        };
+        Some(Selection { day, part })
+    }
+
+    fn matches(&self, solution: &Solution) -> bool {
+        self.day == solution.day && self.part.is_none_or(|p| p == solution.part)
+    }
+}
+
+/// Something to do with a single day's input other than solving it.
+enum Tool {
+    Export { day: u32, path: PathBuf },
+    Show { day: u32 },
+    Play { day: u32 },
+    Record { day: u32, path: PathBuf },
+}
+
+impl Tool {
+    fn day(&self) -> u32 {
+        match self {
+            Tool::Export { day, .. } | Tool::Show { day } | Tool::Play { day } | Tool::Record { day, .. } => *day,
+        }
+    }
+
+    fn run(&self, input: &str) -> Result<(), Box<dyn Error>> {
+        match self {
+            Tool::Export { day, path } => tools::export(*day, input, path),
+            Tool::Show { day } => tools::show(*day, input, &mut io::stdout()),
+            Tool::Play { day } => tools::play(*day, input, &mut io::stdin().lock(), &mut io::stdout()),
+            Tool::Record { day, path } => tools::record(*day, input, path),
+        }
+    }
+}
+
+enum Source {
+    File(PathBuf),
+    Stdin,
+}
+
+struct Options {
+    selections: Vec<Selection>,
+    input: Option<Source>,
+    input_dir: PathBuf,
+    verify: bool,
+    snapshot: Option<PathBuf>,
+    tool: Option<Tool>,
+}
+
+impl Options {
+    fn parse<I: Iterator<Item = String>>(mut args: I) -> Result<Options, String> {
+        let mut options = Options {
+            selections: vec![],
+            input: None,
+            input_dir: PathBuf::from("input/2019"),
+            verify: false,
+            snapshot: None,
+            tool: None,
+        };
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--input" => options.input = match args.next().as_deref() {
+                    Some("-") => Some(Source::Stdin),
+                    Some(path) => Some(Source::File(PathBuf::from(path))),
+                    None => return Err("--input needs a path".to_string()),
+                },
+                "--input-dir" => options.input_dir = match args.next() {
+                    Some(dir) => PathBuf::from(dir),
+                    None => return Err("--input-dir needs a directory".to_string()),
+                },
+                "--verify" => options.verify = true,
+                "--snapshot" => options.snapshot = match args.next() {
+                    Some(path) => Some(PathBuf::from(path)),
+                    None => return Err("--snapshot needs a path".to_string()),
+                },
+                "--export" => options.tool = match (args.next().map(|d| d.parse()), args.next()) {
+                    (Some(Ok(day)), Some(path)) => Some(Tool::Export { day, path: PathBuf::from(path) }),
+                    _ => return Err("--export needs a day and a path".to_string()),
+                },
+                "--show" => options.tool = match args.next().map(|d| d.parse()) {
+                    Some(Ok(day)) => Some(Tool::Show { day }),
+                    _ => return Err("--show needs a day".to_string()),
+                },
+                "--play" => options.tool = match args.next().map(|d| d.parse()) {
+                    Some(Ok(day)) => Some(Tool::Play { day }),
+                    _ => return Err("--play needs a day".to_string()),
+                },
+                "--record" => options.tool = match (args.next().map(|d| d.parse()), args.next()) {
+                    (Some(Ok(day)), Some(path)) => Some(Tool::Record { day, path: PathBuf::from(path) }),
+                    _ => return Err("--record needs a day and a path".to_string()),
+                },
+                "-h" | "--help" => return Err(String::new()),
+                _ => options.selections.push(
+                    Selection::parse(&arg).ok_or_else(|| format!("Invalid day: {}", arg))?),
+            }
+        }
+        if options.tool.is_some() && !options.selections.is_empty() {
+            return Err("--export, --show, --play and --record don't take days to run".to_string());
+        }
+        if let (Some(Tool::Play { .. }), Some(Source::Stdin)) = (&options.tool, &options.input) {
+            return Err("--play reads moves from stdin, so the input can't come from there".to_string());
+        }
+        Ok(options)
+    }
+
+    /// The solutions to run, in day and part order.
+    fn solutions(&self) -> Result<Vec<&'static Solution>, String> {
+        if let Some(unknown) = self.selections.iter().find(|s| !SOLUTIONS.iter().any(|sol| s.matches(sol))) {
+            return Err(match unknown.part {
+                Some(part) => format!("No solution for day {} part {}", unknown.day, part),
+                None => format!("No solutions for day {}", unknown.day),
+            });
+        }
+
+        let selected: Vec<&Solution> =
+            SOLUTIONS.iter()
+                .filter(|sol| self.selections.is_empty() || self.selections.iter().any(|s| s.matches(sol)))
+                .collect();
+        let mut days: Vec<u32> = selected.iter().map(|s| s.day).collect();
+        days.dedup();
+        if self.input.is_some() && days.len() != 1 {
+            return Err("--input needs exactly one day selected".to_string());
+        }
+        Ok(selected)
+    }
+
+    fn read_input(&self, day: u32) -> Result<String, String> {
+        match &self.input {
+            Some(Source::Stdin) => {
+                let mut input = String::new();
+                io::stdin().read_to_string(&mut input).map_err(|e| format!("Can't read stdin: {}", e))?;
+                Ok(input)
+            },
+            Some(Source::File(path)) =>
+                fs::read_to_string(path).map_err(|e| format!("Can't read {}: {}", path.display(), e)),
+            None => {
+                let path = self.input_dir.join(format!("day{}.txt", day));
+                fs::read_to_string(&path).map_err(|e| format!("Can't read {}: {}", path.display(), e))
+            },
+        }
+    }
+}
+
+/// Answers recorded from earlier runs, keyed by day and part, written one `DAY.PART: answer`
+/// per line. Blank lines and lines starting with `#` are ignored.
+struct Snapshot {
+    answers: HashMap<(u32, u32), String>,
+}
+
+impl Snapshot {
+    fn parse(input: &str) -> Result<Snapshot, String> {
+        let mut answers = HashMap::new();
+        for (i, line) in input.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid = || format!("Invalid answer on line {}: {}", i + 1, line);
+            let mut pieces = line.splitn(2, ':');
+            let selection = pieces.next().and_then(Selection::parse).ok_or_else(invalid)?;
+            let (part, answer) = match (selection.part, pieces.next()) {
+                (Some(part), Some(answer)) => (part, answer.trim()),
+                _ => return Err(invalid()),
+            };
+            if answers.insert((selection.day, part), answer.to_string()).is_some() {
+                return Err(format!("Duplicate answer for day {} part {} on line {}", selection.day, part, i + 1));
+            }
+        }
+        Ok(Snapshot { answers })
+    }
+
+    fn load(options: &Options) -> Result<Snapshot, String> {
+        let path = options.snapshot.clone().unwrap_or_else(|| options.input_dir.join("snapshot.txt"));
+        let input = fs::read_to_string(&path).map_err(|e| format!("Can't read {}: {}", path.display(), e))?;
+        Snapshot::parse(&input).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    fn recorded(&self, solution: &Solution) -> Option<&str> {
+        self.answers.get(&(solution.day, solution.part)).map(|a| a.as_str())
+    }
+}
+
+struct Answer {
+    answer: String,
+    generator: Duration,
+    runner: Duration,
+}
+
+fn solve(solution: &Solution, input: &str) -> Result<Answer, Box<dyn Error>> {
+    let start = Instant::now();
+    let runner = (solution.generate)(input).map_err(|e| format!("Invalid input: {}", e))?;
+    let generated = Instant::now();
+    let answer = runner.try_run()?.to_string();
+    let finished = Instant::now();
+    Ok(Answer { answer, generator: generated - start, runner: finished - generated })
+}
+
+fn usage_error(message: &str) -> ! {
+    if !message.is_empty() {
+        eprintln!("{}", message);
+    }
+    eprintln!("{}", USAGE);
+    process::exit(2);
+}
+
+fn main() {
+    let options = Options::parse(env::args().skip(1)).unwrap_or_else(|message| usage_error(&message));
+    if let Some(tool) = &options.tool {
+        let day = tool.day();
+        let result = options.read_input(day).and_then(|input| tool.run(&input).map_err(|e| e.to_string()));
+        if let Err(message) = result {
+            eprintln!("Day {}: {}", day, message);
+            process::exit(1);
+        }
+        return;
+    }
+    let solutions = options.solutions().unwrap_or_else(|message| usage_error(&message));
+
+    let snapshot = if options.verify {
+        match Snapshot::load(&options) {
+            Ok(snapshot) => Some(snapshot),
+            Err(message) => {
+                eprintln!("{}", message);
+                process::exit(2);
+            },
+        }
+    } else {
+        None
+    };
+
+    let start = Instant::now();
+    let mut unverified = 0;
+    let mut inputs: HashMap<u32, Result<String, String>> = HashMap::new();
+    let mut failures = 0;
+    for solution in solutions {
+        let label = format!("Day {} - Part {}", solution.day, solution.part);
+        let input = inputs.entry(solution.day).or_insert_with(|| options.read_input(solution.day));
+        let input = match input {
+            Ok(input) => input,
+            Err(message) => {
+                eprintln!("{}: {}", label, message);
+                failures += 1;
+                continue;
+            },
+        };
+
+        match panic::catch_unwind(AssertUnwindSafe(|| solve(solution, input))) {
+            Ok(Ok(answer)) => {
+                println!(
+                    "{}: {}\n\tgenerator: {:?}, runner: {:?}",
+                    label, answer.answer, answer.generator, answer.runner);
+                match snapshot.as_ref().map(|s| s.recorded(solution)) {
+                    Some(Some(recorded)) if recorded != answer.answer => {
+                        eprintln!("{}: CHANGED: snapshot has {}, got {}", label, recorded, answer.answer);
+                        failures += 1;
+                    },
+                    Some(None) => unverified += 1,
+                    _ => {},
+                }
+            },
+            Ok(Err(e)) => {
+                eprintln!("{}: FAILED: {}", label, e);
+                failures += 1;
+            },
+            Err(_) => {
+                eprintln!("{}: PANICKED", label);
+                failures += 1;
+            },
+        }
+    }
+    println!("Total: {:?}", start.elapsed());
+    if unverified > 0 {
+        eprintln!("{} not in the snapshot", unverified);
+    }
+
+    if failures > 0 {
+        eprintln!("{} failed", failures);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,197 @@
+/// Letters 4 pixels wide (5 for `Y`) and 6 tall, as drawn by most puzzles.
+const SMALL_FONT: &[(char, &str)] = &[
+    ('A', ".##.\n#..#\n#..#\n####\n#..#\n#..#"),
+    ('B', "###.\n#..#\n###.\n#..#\n#..#\n###."),
+    ('C', ".##.\n#..#\n#...\n#...\n#..#\n.##."),
+    ('E', "####\n#...\n###.\n#...\n#...\n####"),
+    ('F', "####\n#...\n###.\n#...\n#...\n#..."),
+    ('G', ".##.\n#..#\n#...\n#.##\n#..#\n.###"),
+    ('H', "#..#\n#..#\n####\n#..#\n#..#\n#..#"),
+    ('I', ".###\n..#.\n..#.\n..#.\n..#.\n.###"),
+    ('J', "..##\n...#\n...#\n...#\n#..#\n.##."),
+    ('K', "#..#\n#.#.\n##..\n#.#.\n#.#.\n#..#"),
+    ('L', "#...\n#...\n#...\n#...\n#...\n####"),
+    ('O', ".##.\n#..#\n#..#\n#..#\n#..#\n.##."),
+    ('P', "###.\n#..#\n#..#\n###.\n#...\n#..."),
+    ('R', "###.\n#..#\n#..#\n###.\n#.#.\n#..#"),
+    ('S', ".###\n#...\n#...\n.##.\n...#\n###."),
+    ('U', "#..#\n#..#\n#..#\n#..#\n#..#\n.##."),
+    ('Y', "#...#\n#...#\n.#.#.\n..#..\n..#..\n..#.."),
+    ('Z', "####\n...#\n..#.\n.#..\n#...\n####"),
+];
+
+/// Letters 6 pixels wide and 10 tall.
+const LARGE_FONT: &[(char, &str)] = &[
+    ('A', "..##..\n.#..#.\n#....#\n#....#\n#....#\n######\n#....#\n#....#\n#....#\n#....#"),
+    ('B', "#####.\n#....#\n#....#\n#....#\n#####.\n#....#\n#....#\n#....#\n#....#\n#####."),
+    ('C', ".####.\n#....#\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#....#\n.####."),
+    ('E', "######\n#.....\n#.....\n#.....\n#####.\n#.....\n#.....\n#.....\n#.....\n######"),
+    ('F', "######\n#.....\n#.....\n#.....\n#####.\n#.....\n#.....\n#.....\n#.....\n#....."),
+    ('G', ".####.\n#....#\n#.....\n#.....\n#.....\n#..###\n#....#\n#....#\n#...##\n.###.#"),
+    ('H', "#....#\n#....#\n#....#\n#....#\n######\n#....#\n#....#\n#....#\n#....#\n#....#"),
+    ('J', "...###\n....#.\n....#.\n....#.\n....#.\n....#.\n....#.\n#...#.\n#...#.\n.###.."),
+    ('K', "#....#\n#...#.\n#..#..\n#.#...\n##....\n##....\n#.#...\n#..#..\n#...#.\n#....#"),
+    ('L', "#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n######"),
+    ('N', "#....#\n##...#\n##...#\n#.#..#\n#.#..#\n#..#.#\n#..#.#\n#...##\n#...##\n#....#"),
+    ('P', "#####.\n#....#\n#....#\n#....#\n#####.\n#.....\n#.....\n#.....\n#.....\n#....."),
+    ('R', "#####.\n#....#\n#....#\n#....#\n#####.\n#..#..\n#...#.\n#...#.\n#....#\n#....#"),
+    ('X', "#....#\n#....#\n.#..#.\n.#..#.\n..##..\n..##..\n.#..#.\n.#..#.\n#....#\n#....#"),
+    ('Z', "######\n.....#\n.....#\n....#.\n...#..\n..#...\n.#....\n#.....\n#.....\n######"),
+];
+
+/// Pixels as rows of on/off values.
+pub type Image = Vec<Vec<bool>>;
+
+/// Parses drawn text where `on` marks lit pixels and anything else is dark.
+pub fn parse_art(art: &str, on: char) -> Image {
+    art.lines()
+        .filter(|l| !l.is_empty())
+        .map(|l| l.chars().map(|c| c == on).collect())
+        .collect()
+}
+
+/// Draws an image using `on` for lit pixels and spaces for dark ones.
+pub fn render(image: &[Vec<bool>], on: char) -> String {
+    let mut art = String::new();
+    for row in image {
+        art.extend(row.iter().map(|lit| if *lit { on } else { ' ' }));
+        art.push('\n');
+    }
+    art
+}
+
+/// The letters in the image, or `None` if it contains anything that isn't a known glyph.
+pub fn recognize(image: &[Vec<bool>]) -> Option<String> {
+    let image = trim_rows(image);
+    let font = match image.len() {
+        6 => SMALL_FONT,
+        10 => LARGE_FONT,
+        _ => return None,
+    };
+
+    let glyphs = split_glyphs(&image);
+    if glyphs.is_empty() {
+        return None;
+    }
+    glyphs.iter()
+        .map(|glyph| {
+            font.iter()
+                .find(|(_, art)| trim_columns(&parse_art(art, '#')) == *glyph)
+                .map(|(letter, _)| *letter)
+        })
+        .collect()
+}
+
+/// The letters in the image, falling back to drawing it (with a leading newline, so it lines
+/// up when printed as an answer) when the glyphs aren't recognized.
+pub fn read(image: &[Vec<bool>]) -> String {
+    recognize(image).unwrap_or_else(|| format!("\n{}", render(image, '▓')))
+}
+
+fn trim_rows(image: &[Vec<bool>]) -> Image {
+    let lit = |row: &Vec<bool>| row.iter().any(|p| *p);
+    match (image.iter().position(lit), image.iter().rposition(lit)) {
+        (Some(first), Some(last)) => image[first..=last].to_vec(),
+        _ => vec![],
+    }
+}
+
+fn column_lit(image: &[Vec<bool>], x: usize) -> bool {
+    image.iter().any(|row| *row.get(x).unwrap_or(&false))
+}
+
+fn trim_columns(image: &[Vec<bool>]) -> Image {
+    let width = image.iter().map(|r| r.len()).max().unwrap_or(0);
+    let columns: Vec<usize> = (0..width).filter(|x| column_lit(image, *x)).collect();
+    match (columns.first(), columns.last()) {
+        (Some(first), Some(last)) => image.iter()
+            .map(|row| (*first..=*last).map(|x| *row.get(x).unwrap_or(&false)).collect())
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// Splits the image into runs of columns separated by fully dark columns.
+fn split_glyphs(image: &[Vec<bool>]) -> Vec<Image> {
+    let width = image.iter().map(|r| r.len()).max().unwrap_or(0);
+    let mut glyphs = vec![];
+    let mut start = None;
+    for x in 0..=width {
+        match (start, x < width && column_lit(image, x)) {
+            (None, true) => start = Some(x),
+            (Some(first), false) => {
+                glyphs.push(image.iter()
+                    .map(|row| (first..x).map(|x| *row.get(x).unwrap_or(&false)).collect())
+                    .collect());
+                start = None;
+            },
+            _ => {},
+        }
+    }
+    glyphs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Draws `letters` from `font` side by side, a dark column apart, with dark rows around.
+    fn draw(font: &[(char, &str)], letters: &str) -> Image {
+        let glyphs: Vec<Image> = letters.chars()
+            .map(|l| parse_art(font.iter().find(|(c, _)| *c == l).unwrap().1, '#'))
+            .collect();
+        let width: usize = glyphs.iter().map(|g| g[0].len() + 1).sum();
+        let mut image = vec![vec![false; width + 1]];
+        for y in 0..glyphs[0].len() {
+            let mut row = vec![false];
+            for glyph in &glyphs {
+                row.extend(&glyph[y]);
+                row.push(false);
+            }
+            image.push(row);
+        }
+        image.push(vec![false; width + 1]);
+        image
+    }
+
+    #[test]
+    fn reads_small_letters() {
+        assert_eq!(recognize(&draw(SMALL_FONT, "CYPHER")), Some("CYPHER".to_string()));
+        let art = "\
+            .##..####.#..#.\n\
+            #..#.#....#..#.\n\
+            #..#.###..####.\n\
+            ####.#....#..#.\n\
+            #..#.#....#..#.\n\
+            #..#.####.#..#.";
+        assert_eq!(read(&parse_art(art, '#')), "AEH");
+    }
+
+    #[test]
+    fn reads_large_letters() {
+        assert_eq!(recognize(&draw(LARGE_FONT, "ZNXJ")), Some("ZNXJ".to_string()));
+        let art = "\
+            #.......#....#\n\
+            #.......#....#\n\
+            #........#..#.\n\
+            #........#..#.\n\
+            #.........##..\n\
+            #.........##..\n\
+            #........#..#.\n\
+            #........#..#.\n\
+            #.......#....#\n\
+            ######..#....#";
+        assert_eq!(read(&parse_art(art, '#')), "LX");
+    }
+
+    #[test]
+    fn draws_unknown_glyphs() {
+        let art = "#.#.\n.#..\n#.#.\n....\n....\n####";
+        let image = parse_art(art, '#');
+        assert_eq!(recognize(&image), None);
+        assert_eq!(read(&image), "\n▓ ▓ \n ▓  \n▓ ▓ \n    \n    \n▓▓▓▓\n");
+
+        assert_eq!(recognize(&parse_art("#\n#", '#')), None);
+        assert_eq!(recognize(&vec![vec![false; 4]; 6]), None);
+    }
+}
@@ -0,0 +1,93 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// Puzzle input that couldn't be parsed, pointing at the offending text. Lines and columns
+/// count from 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub text: String,
+    pub message: String,
+}
+
+impl ParseError {
+    pub fn new(line: usize, column: usize, text: &str, message: &str) -> ParseError {
+        ParseError { line, column, text: text.to_string(), message: message.to_string() }
+    }
+
+    /// Error for `text`, which must be a slice of `input`. Its line and column come from where
+    /// the slice starts.
+    pub fn at(input: &str, text: &str, message: &str) -> ParseError {
+        let offset = text.as_ptr() as usize - input.as_ptr() as usize;
+        assert!(offset <= input.len(), "Text must be a slice of the input!");
+
+        let before = &input[..offset];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        let column = before[line_start..].chars().count() + 1;
+        ParseError::new(line, column, text, message)
+    }
+
+    /// Error for the end of `input`, for things that are missing altogether.
+    pub fn at_end(input: &str, message: &str) -> ParseError {
+        ParseError::at(input, &input[input.len()..], message)
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at line {}, column {}: {:?}", self.message, self.line, self.column, self.text)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parses `text`, a slice of `input`, as a number.
+pub fn parse_number<T: FromStr>(input: &str, text: &str) -> Result<T, ParseError> {
+    text.parse().map_err(|_| ParseError::at(input, text, "Invalid number"))
+}
+
+/// The lines of `input` with surrounding whitespace removed, skipping blank ones. The lines
+/// are still slices of `input`, so errors about them can point at where they are.
+pub fn lines(input: &str) -> impl Iterator<Item = &str> {
+    input.lines().map(|l| l.trim()).filter(|l| !l.is_empty())
+}
+
+/// Fails on the first character of `input`, other than line breaks, that isn't `allowed`.
+pub fn check_chars<F: Fn(char) -> bool>(input: &str, allowed: F, message: &str) -> Result<(), ParseError> {
+    match input.char_indices().find(|(_, c)| *c != '\n' && *c != '\r' && !allowed(*c)) {
+        Some((i, c)) => Err(ParseError::at(input, &input[i..i + c.len_utf8()], message)),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locates_text() {
+        let input = "12\n 3x4\n5";
+        let error = ParseError::at(input, &input[4..7], "Invalid number");
+        assert_eq!(error, ParseError::new(2, 2, "3x4", "Invalid number"));
+        assert_eq!(error.to_string(), "Invalid number at line 2, column 2: \"3x4\"");
+
+        assert_eq!(ParseError::at_end(input, "Missing"), ParseError::new(3, 2, "", "Missing"));
+    }
+
+    #[test]
+    fn parses_numbers_in_place() {
+        let input = "7\n\n  -8  \n9a";
+        let numbers: Vec<Result<i32, ParseError>> = lines(input).map(|l| parse_number(input, l)).collect();
+        assert_eq!(numbers, vec![Ok(7), Ok(-8), Err(ParseError::new(4, 1, "9a", "Invalid number"))]);
+    }
+
+    #[test]
+    fn finds_unexpected_chars() {
+        assert_eq!(check_chars("#.\r\n.#", |c| c == '#' || c == '.', "Invalid cell"), Ok(()));
+        assert_eq!(
+            check_chars("#.\n.é#", |c| c == '#' || c == '.', "Invalid cell"),
+            Err(ParseError::new(2, 2, "é", "Invalid cell")));
+    }
+}
@@ -0,0 +1,261 @@
+use num::Zero;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::hash::Hash;
+use std::ops::Add;
+
+/// A route found by a search: every state from the start to the goal, inclusive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path<S, C> {
+    pub states: Vec<S>,
+    pub cost: C,
+}
+
+impl<S, C> Path<S, C> {
+    pub fn goal(&self) -> &S {
+        self.states.last().expect("Paths include their start!")
+    }
+}
+
+fn reconstruct<S, C>(goal: S, cost: C, parents: &HashMap<S, Option<S>>) -> Path<S, C>
+where
+    S: Clone + Eq + Hash,
+{
+    let mut states = vec![goal];
+    while let Some(Some(parent)) = parents.get(states.last().unwrap()) {
+        states.push(parent.clone());
+    }
+    states.reverse();
+    Path { states, cost }
+}
+
+/// Shortest path by number of steps from `start` to the first state satisfying `is_goal`.
+pub fn bfs<S, N, I, G>(start: S, mut neighbors: N, mut is_goal: G) -> Option<Path<S, usize>>
+where
+    S: Clone + Eq + Hash,
+    N: FnMut(&S) -> I,
+    I: IntoIterator<Item = S>,
+    G: FnMut(&S) -> bool,
+{
+    let mut parents = HashMap::new();
+    let mut open = VecDeque::new();
+    parents.insert(start.clone(), None);
+    open.push_back((start, 0));
+
+    while let Some((state, steps)) = open.pop_front() {
+        if is_goal(&state) {
+            return Some(reconstruct(state, steps, &parents));
+        }
+        for next in neighbors(&state) {
+            if !parents.contains_key(&next) {
+                parents.insert(next.clone(), Some(state.clone()));
+                open.push_back((next, steps + 1));
+            }
+        }
+    }
+    None
+}
+
+/// Steps from `start` to every reachable state.
+pub fn bfs_distances<S, N, I>(start: S, mut neighbors: N) -> HashMap<S, usize>
+where
+    S: Clone + Eq + Hash,
+    N: FnMut(&S) -> I,
+    I: IntoIterator<Item = S>,
+{
+    let mut distances = HashMap::new();
+    let mut open = VecDeque::new();
+    distances.insert(start.clone(), 0);
+    open.push_back(start);
+
+    while let Some(state) = open.pop_front() {
+        let steps = distances[&state];
+        for next in neighbors(&state) {
+            if !distances.contains_key(&next) {
+                distances.insert(next.clone(), steps + 1);
+                open.push_back(next);
+            }
+        }
+    }
+    distances
+}
+
+/// Cheapest path from `start` to the first state satisfying `is_goal`, where `neighbors`
+/// yields each next state along with the cost of moving to it. Costs must not be negative.
+pub fn dijkstra<S, C, N, I, G>(start: S, neighbors: N, is_goal: G) -> Option<Path<S, C>>
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Ord + Zero + Add<Output = C>,
+    N: FnMut(&S) -> I,
+    I: IntoIterator<Item = (S, C)>,
+    G: FnMut(&S) -> bool,
+{
+    astar(start, neighbors, |_| C::zero(), is_goal)
+}
+
+/// Cheapest cost from `start` to every reachable state.
+pub fn dijkstra_distances<S, C, N, I>(start: S, mut neighbors: N) -> HashMap<S, C>
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Ord + Zero + Add<Output = C>,
+    N: FnMut(&S) -> I,
+    I: IntoIterator<Item = (S, C)>,
+{
+    let mut costs = HashMap::new();
+    let mut open = BinaryHeap::new();
+    costs.insert(start.clone(), C::zero());
+    open.push(Entry { priority: C::zero(), cost: C::zero(), state: start });
+
+    while let Some(Entry { cost, state, .. }) = open.pop() {
+        if costs.get(&state).is_some_and(|best| cost > *best) {
+            continue;
+        }
+        for (next, step_cost) in neighbors(&state) {
+            let next_cost = cost + step_cost;
+            if costs.get(&next).is_none_or(|best| next_cost < *best) {
+                costs.insert(next.clone(), next_cost);
+                open.push(Entry { priority: next_cost, cost: next_cost, state: next });
+            }
+        }
+    }
+    costs
+}
+
+/// Like `dijkstra`, but explores states in order of cost so far plus `heuristic`. The
+/// heuristic must never overestimate the remaining cost for the path to be the cheapest.
+pub fn astar<S, C, N, I, H, G>(start: S, mut neighbors: N, mut heuristic: H, mut is_goal: G) -> Option<Path<S, C>>
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Ord + Zero + Add<Output = C>,
+    N: FnMut(&S) -> I,
+    I: IntoIterator<Item = (S, C)>,
+    H: FnMut(&S) -> C,
+    G: FnMut(&S) -> bool,
+{
+    let mut costs = HashMap::new();
+    let mut parents = HashMap::new();
+    let mut open = BinaryHeap::new();
+    costs.insert(start.clone(), C::zero());
+    parents.insert(start.clone(), None);
+    open.push(Entry { priority: heuristic(&start), cost: C::zero(), state: start });
+
+    while let Some(Entry { cost, state, .. }) = open.pop() {
+        if costs.get(&state).is_some_and(|best| cost > *best) {
+            continue;
+        }
+        if is_goal(&state) {
+            return Some(reconstruct(state, cost, &parents));
+        }
+        for (next, step_cost) in neighbors(&state) {
+            let next_cost = cost + step_cost;
+            if costs.get(&next).is_none_or(|best| next_cost < *best) {
+                costs.insert(next.clone(), next_cost);
+                parents.insert(next.clone(), Some(state.clone()));
+                let priority = next_cost + heuristic(&next);
+                open.push(Entry { priority, cost: next_cost, state: next });
+            }
+        }
+    }
+    None
+}
+
+/// Heap entry ordered so that the lowest priority is popped first.
+struct Entry<S, C> {
+    priority: C,
+    cost: C,
+    state: S,
+}
+
+impl<S, C: Ord> PartialEq for Entry<S, C> {
+    fn eq(&self, other: &Entry<S, C>) -> bool {
+        self.priority == other.priority
+    }
+}
+
+impl<S, C: Ord> Eq for Entry<S, C> {}
+
+impl<S, C: Ord> PartialOrd for Entry<S, C> {
+    fn partial_cmp(&self, other: &Entry<S, C>) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<S, C: Ord> Ord for Entry<S, C> {
+    fn cmp(&self, other: &Entry<S, C>) -> Ordering {
+        other.priority.cmp(&self.priority)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The direct edge to 'd' is the fewest steps but not the cheapest way there. 'e' has no
+    // way in.
+    const EDGES: &[(char, char, u32)] = &[
+        ('a', 'b', 1), ('b', 'c', 1), ('c', 'd', 1), ('a', 'd', 10), ('d', 'a', 1), ('e', 'a', 1),
+    ];
+
+    fn weighted(state: &char) -> Vec<(char, u32)> {
+        EDGES.iter().filter(|(from, _, _)| from == state).map(|(_, to, cost)| (*to, *cost)).collect()
+    }
+
+    fn unweighted(state: &char) -> Vec<char> {
+        weighted(state).into_iter().map(|(to, _)| to).collect()
+    }
+
+    #[test]
+    fn heap_pops_lowest_priority_first() {
+        let mut heap = BinaryHeap::new();
+        for (priority, state) in [(3, 'c'), (1, 'a'), (4, 'd'), (2, 'b')].iter() {
+            heap.push(Entry { priority: *priority, cost: 0, state: *state });
+        }
+        let order: Vec<char> = std::iter::from_fn(|| heap.pop()).map(|e| e.state).collect();
+        assert_eq!(order, vec!['a', 'b', 'c', 'd']);
+    }
+
+    #[test]
+    fn bfs_takes_fewest_steps() {
+        let path = bfs('a', unweighted, |s| *s == 'd').unwrap();
+        assert_eq!(path, Path { states: vec!['a', 'd'], cost: 1 });
+        assert_eq!(path.goal(), &'d');
+        assert_eq!(bfs('a', unweighted, |s| *s == 'a').unwrap().states, vec!['a']);
+    }
+
+    #[test]
+    fn dijkstra_takes_cheapest_path() {
+        let path = dijkstra('a', weighted, |s| *s == 'd').unwrap();
+        assert_eq!(path, Path { states: vec!['a', 'b', 'c', 'd'], cost: 3 });
+        assert_eq!(dijkstra('b', weighted, |s| *s == 'a').unwrap(), Path { states: vec!['b', 'c', 'd', 'a'], cost: 3 });
+    }
+
+    #[test]
+    fn unreachable_goals() {
+        assert_eq!(bfs('a', unweighted, |s| *s == 'e'), None);
+        assert_eq!(dijkstra('a', weighted, |s| *s == 'e'), None);
+        assert_eq!(astar('a', weighted, |_| 0, |s| *s == 'e'), None);
+    }
+
+    #[test]
+    fn astar_follows_heuristic() {
+        // A line of states from 0 to 20 with unit steps. A perfect heuristic only ever
+        // expands states on the way to the goal.
+        let mut expanded = vec![];
+        let neighbors = |s: &i32| {
+            expanded.push(*s);
+            vec![(s - 1, 1), (s + 1, 1)].into_iter().filter(|(n, _)| (0..=20).contains(n))
+        };
+        let path = astar(10, neighbors, |s| (15 - s).abs(), |s| *s == 15).unwrap();
+        assert_eq!(path, Path { states: (10..=15).collect(), cost: 5 });
+        assert_eq!(expanded, vec![10, 11, 12, 13, 14]);
+    }
+
+    #[test]
+    fn distances_to_every_reachable_state() {
+        let steps = bfs_distances('a', unweighted);
+        assert_eq!(steps, [('a', 0), ('b', 1), ('c', 2), ('d', 1)].iter().cloned().collect());
+        let costs = dijkstra_distances('a', weighted);
+        assert_eq!(costs, [('a', 0), ('b', 1), ('c', 2), ('d', 3)].iter().cloned().collect());
+        assert_eq!(dijkstra_distances('e', weighted)[&'d'], 4);
+    }
+}
@@ -0,0 +1,18 @@
+use aoc_runner::{ArcStr, Runner};
+use std::error::Error;
+
+use crate::*;
+
+/// Parses the input, returning something that can compute the answer.
+pub type Generator = fn(&str) -> Result<Box<dyn Runner>, Box<dyn Error>>;
+
+pub struct Solution {
+    pub day: u32,
+    pub part: u32,
+    pub generate: Generator,
+}
+
+/// Every solution registered with `#[aoc]`, in day and part order, found by `build.rs`. Each
+/// entry calls the `Factory::dayN_partM` constructor that `aoc_lib!` generates in lib.rs, which
+/// aoc-runner doesn't document; see `build.rs` before upgrading it.
+pub const SOLUTIONS: &[Solution] = include!(concat!(env!("OUT_DIR"), "/solutions.rs"));
@@ -0,0 +1,26 @@
+//! Helpers shared by the unit tests and the integration tests in `tests/`.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A path in the system's temporary directory, unique to this process, that is deleted when
+/// the guard is dropped, whether or not the test got far enough to create it.
+pub struct TempFile {
+    path: PathBuf,
+}
+
+impl TempFile {
+    pub fn new(name: &str) -> TempFile {
+        TempFile { path: std::env::temp_dir().join(format!("aoc2019-{}-{}", std::process::id(), name)) }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
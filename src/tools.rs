@@ -0,0 +1,51 @@
+//! Ways to look at a puzzle beyond its answer, for the days that have them.
+
+use std::error::Error;
+use std::io::{BufRead, Write};
+use std::path::Path;
+
+/// Writes a picture of the puzzle to `path`: the orbit map as Graphviz DOT for day 6, the
+/// decoded image as a PGM file for day 8, and the painted hull as a PBM file for day 11.
+pub fn export(day: u32, input: &str, path: &Path) -> Result<(), Box<dyn Error>> {
+    match day {
+        6 => crate::day6::export(input, path),
+        8 => crate::day8::export(input, path),
+        11 => crate::day11::export(input, path),
+        _ => Err(unsupported("export")),
+    }
+}
+
+/// Draws the puzzle in a terminal: the decoded image in color for day 8, and a replay of the
+/// robot painting the hull for day 11.
+pub fn show(day: u32, input: &str, out: &mut dyn Write) -> Result<(), Box<dyn Error>> {
+    match day {
+        8 => crate::day8::show(input, out),
+        11 => crate::day11::show(input, out),
+        _ => Err(unsupported("show")),
+    }
+}
+
+/// Plays the puzzle's game on `display`: the arcade cabinet for day 13, steered by lines of
+/// `a` (left), `d` (right) or anything else (stay) from `controls`, or `auto` to let it play
+/// itself.
+pub fn play(day: u32, input: &str, controls: &mut dyn BufRead, display: &mut dyn Write) -> Result<(), Box<dyn Error>> {
+    match day {
+        13 => crate::day13::play(input, controls, display),
+        _ => Err(unsupported("play")),
+    }
+}
+
+/// Saves the Intcode I/O of a run to `path`, as a `Session` that can be replayed against the
+/// program later: the robot painting the identifier for day 11, and the auto-pilot beating the
+/// game for day 13.
+pub fn record(day: u32, input: &str, path: &Path) -> Result<(), Box<dyn Error>> {
+    match day {
+        11 => crate::day11::record(input, path),
+        13 => crate::day13::record(input, path),
+        _ => Err(unsupported("record")),
+    }
+}
+
+fn unsupported(tool: &str) -> Box<dyn Error> {
+    format!("Nothing to {} for this day", tool).into()
+}
@@ -0,0 +1,17 @@
+//! Helpers shared by the integration tests.
+
+use std::process::{Command, Output};
+
+#[path = "../../src/test_support.rs"]
+mod test_support;
+
+pub use self::test_support::TempFile;
+
+/// Runs the `aoc2019` binary from the repository root, so it finds the real inputs.
+pub fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_aoc2019"))
+        .args(args)
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .output()
+        .expect("Couldn't run aoc2019!")
+}
@@ -0,0 +1,27 @@
+//! Runs every solution on the real inputs and checks the answers against the regression
+//! snapshot in `input/2019/snapshot.txt`.
+
+mod common;
+
+use common::{run, TempFile};
+use std::fs;
+
+#[test]
+fn every_answer_matches_snapshot() {
+    let output = run(&["--verify"]);
+    let errors = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{}", errors);
+    assert!(!errors.contains("not in the snapshot"), "{}", errors);
+}
+
+#[test]
+fn reports_changed_answers() {
+    let snapshot = TempFile::new("snapshot.txt");
+    fs::write(snapshot.path(), "1.1: 3254441\n1.2: 0\n").unwrap();
+    let output = run(&["1", "--verify", "--snapshot", snapshot.path().to_str().unwrap()]);
+
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "Day 1 - Part 2: CHANGED: snapshot has 0, got 4878818\n1 failed\n");
+}
@@ -0,0 +1,76 @@
+//! Runs the tools that go beyond answers on the real inputs.
+
+mod common;
+
+use aoc2019::intcode_computer::{Event, Session};
+use common::{run, TempFile};
+use std::fs;
+
+fn export(day: &str, name: &str) -> String {
+    let file = TempFile::new(name);
+    let output = run(&["--export", day, file.path().to_str().unwrap()]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    fs::read_to_string(file.path()).unwrap()
+}
+
+#[test]
+fn exports_orbit_map() {
+    let dot = export("6", "orbits.dot");
+    assert!(dot.starts_with("digraph orbits {\n    \"COM\" [shape=doublecircle];\n"), "{}", dot);
+    assert!(dot.ends_with("}\n"));
+}
+
+#[test]
+fn rejects_days_without_tools() {
+    let file = TempFile::new("none");
+    let output = run(&["--export", "1", file.path().to_str().unwrap()]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(String::from_utf8_lossy(&output.stderr), "Day 1: Nothing to export for this day\n");
+}
+
+#[test]
+fn exports_decoded_image() {
+    let pgm = export("8", "image.pgm");
+    assert!(pgm.starts_with("P2\n250 60\n255\n"), "{}", &pgm[..20]);
+    assert_eq!(pgm.lines().count(), 3 + 60);
+}
+
+#[test]
+fn shows_decoded_image() {
+    let output = run(&["--show", "8"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let drawing = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(drawing.lines().count(), 6);
+    assert!(drawing.lines().all(|l| l.ends_with("\x1b[0m")));
+}
+
+#[test]
+fn exports_painted_hull() {
+    let pbm = export("11", "hull.pbm");
+    assert!(pbm.starts_with("P1\n"), "{}", pbm);
+    let rows: Vec<&str> = pbm.lines().skip(2).collect();
+    assert_eq!(rows.len(), 6);
+    assert!(rows.iter().all(|r| r.split(' ').all(|bit| bit == "0" || bit == "1")));
+}
+
+#[test]
+fn plays_arcade_game() {
+    // Without any moves on stdin, the auto-pilot plays the whole game.
+    let output = run(&["--play", "13"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let screen = String::from_utf8_lossy(&output.stdout);
+    assert!(screen.ends_with("Game over! Final score: 19297\n"), "{}", &screen[screen.len() - 200..]);
+}
+
+#[test]
+fn records_sessions() {
+    for day in &["11", "13"] {
+        let file = TempFile::new(&format!("day{}.session", day));
+        let output = run(&["--record", day, file.path().to_str().unwrap()]);
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+        let session = Session::load(file.path()).unwrap();
+        assert!(session.inputs().count() > 0, "day {}", day);
+        assert_eq!(session.events().last(), Some(&Event::Halt));
+    }
+}